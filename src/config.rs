@@ -6,8 +6,8 @@
 use clap::Parser;
 
 use crate::presets::Preset;
-use crate::process::CommentPattern;
-use crate::Format;
+use crate::process::{BlockPattern, CommentPattern, Ellipsis, Nesting};
+use crate::{find_grammar, BlockOptions, DocMarkup, Format, Output, Template, GRAMMARS};
 use regex::Regex;
 
 /// Command line interface
@@ -25,48 +25,293 @@ use regex::Regex;
 )]
 pub struct CliArgs {
     /// The input file to parse
-    #[cfg_attr(feature = "cli", arg(required = true))]
+    #[cfg_attr(
+        feature = "cli",
+        arg(
+            required_unless_present = "render",
+            default_value = "",
+            hide_default_value = true
+        )
+    )]
     pub file: String,
 
     /// The component search path
     ///
     /// Each path is a case-sensitive substring used to search for components at that level.
     /// The first line of the code after the doc comments is searched for the substring.
-    #[cfg_attr(feature = "cli", arg(required = true))]
+    ///
+    /// More search paths can be added with --and. The found components are printed in
+    /// source order, and the shared context is only printed once.
+    ///
+    /// Can be omitted with --examples-dir to write the examples of the whole file.
+    #[cfg_attr(
+        feature = "cli",
+        arg(required_unless_present_any = ["examples_dir", "render"])
+    )]
     pub search_path: Vec<String>,
 
+    /// Search paths added with `--and`
+    #[cfg_attr(feature = "cli", command(flatten))]
+    pub and: AndSearchPaths,
+
     /// Outer single line comment regex
     #[cfg_attr(feature = "cli", arg(long))]
     outer: Option<String>,
 
     /// Outer multi line comment start regex
+    ///
+    /// Can be repeated with --outer-end for multiple pairs of delimiters.
     #[cfg_attr(feature = "cli", arg(long))]
-    outer_start: Option<String>,
+    outer_start: Vec<String>,
 
     /// Outer multi line comment end regex
+    ///
+    /// Named groups that are also in the start regex must match the same text,
+    /// like `(?P<level>=*)` in `--outer-start '^--\[(?P<level>=*)\['` and `--outer-end '\](?P<level>=*)\]'`
+    /// for Lua long brackets.
     #[cfg_attr(feature = "cli", arg(long))]
-    outer_end: Option<String>,
+    outer_end: Vec<String>,
+
+    /// Outer multi line comment regex for the start of nested comments, if they can be nested
+    ///
+    /// The comment ends where the number of --outer-end matches is the same as this.
+    #[cfg_attr(feature = "cli", arg(long))]
+    outer_nested: Option<String>,
 
     /// Inner single line comment regex
     #[cfg_attr(feature = "cli", arg(long))]
     inner: Option<String>,
 
     /// Inner multi line comment start regex
+    ///
+    /// Can be repeated with --inner-end for multiple pairs of delimiters.
     #[cfg_attr(feature = "cli", arg(long))]
-    inner_start: Option<String>,
+    inner_start: Vec<String>,
 
     /// Inner multi line comment end regex
     #[cfg_attr(feature = "cli", arg(long))]
-    inner_end: Option<String>,
+    inner_end: Vec<String>,
+
+    /// Inner multi line comment regex for the start of nested comments, if they can be nested
+    #[cfg_attr(feature = "cli", arg(long))]
+    inner_nested: Option<String>,
+
+    /// Regex for lines that are allowed between the blocks of outer comments, like `// SAFETY:`
+    ///
+    /// Single line and multi line outer comments can be mixed for the same component.
+    /// These lines are kept in the outer comments if another block follows them.
+    #[cfg_attr(feature = "cli", arg(long))]
+    transparent: Option<String>,
+
+    /// Regex for trailing doc comments after the code, like `///<` in `Red, ///< the red channel`
+    ///
+    /// The regex is matched at the start of the line or after a space or tab.
+    /// The line becomes a component without children, with the trailing comment as its
+    /// comment. A trailing comment on its own line is for the line above.
+    /// Default is the pattern of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    trailing: Option<String>,
 
     /// Pattern for lines that should be ignored
     #[cfg_attr(feature = "cli", arg(long, short))]
     ignore: Vec<String>,
 
+    /// Regex for attribute lines between the doc comments and the component, like `#[derive(Debug)]`
+    ///
+    /// An attribute continues to the next lines until the brackets in it are balanced.
+    /// The attributes are not searched, and are printed before the first line of the component.
+    /// Default is the pattern of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    attribute: Option<String>,
+
+    /// Hide the attributes of the components, except in the detail and code formats
+    #[cfg_attr(feature = "cli", arg(long))]
+    hide_attributes: bool,
+
+    /// Only count the outer comments that end directly above a declaration
+    ///
+    /// This is for languages like Go, where doc comments are regular comments before
+    /// the declarations. A line that matches --declaration must be directly below the outer
+    /// comments (after the attributes), so other comments in the code do not start components.
+    /// Default is the adjacency of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    adjacent: bool,
+
+    /// Regex for the first line of declarations, like `^(func|type|var|const) `
+    ///
+    /// Used by --adjacent. Default is the pattern of the preset if a preset is used.
+    /// If not set, any line directly below the outer comments is a declaration.
+    #[cfg_attr(feature = "cli", arg(long))]
+    declaration: Option<String>,
+
+    /// Regex for the first line of items, like `^(pub )?(fn|struct|impl|mod) `
+    ///
+    /// Lines that match and are not indented start components even without outer comments,
+    /// so undocumented code can be searched. The outer comments and attributes above still
+    /// attach to the component. In the bodies of functions that match --function-pattern, only
+    /// nested functions are items, so local variables are not components.
+    /// Default is the pattern of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    item: Option<String>,
+
+    /// Regex for section header comments, like `^// MARK:` or `^/// # `
+    ///
+    /// A line that matches and is not an outer comment of a component starts a section, which
+    /// is a component that groups the components up to the next section or region, or the end
+    /// of the region.
+    /// The section is searched by the header, and its summary is an outline of the members.
+    /// Default is the pattern of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    section: Option<String>,
+
+    /// Regex for the start of regions, like `^#region`
+    ///
+    /// A region is a section that ends at the matching --region-end, and regions can be nested.
+    /// Default is the pattern of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long, requires = "region_end"))]
+    region_start: Option<String>,
+
+    /// Regex for the end of regions, like `^#endregion`. See --region-start
+    #[cfg_attr(feature = "cli", arg(long, requires = "region_start"))]
+    region_end: Option<String>,
+
+    /// Regex for the last line of multi-line signatures, like `{` at the end of the line
+    ///
+    /// The lines of the signature are kept in the summary, even if they are indented.
+    /// A line only ends the signature if the parentheses and square brackets are balanced.
+    /// Default is the pattern of the preset if a preset is used.
+    /// If not set, the signature ends at the first line where the brackets are balanced.
+    #[cfg_attr(feature = "cli", arg(long))]
+    signature_end: Option<String>,
+
+    /// How to find the children of components
+    ///
+    /// `indent` finds children in the indented blocks. `brace` finds children inside
+    /// the `{` and `}` of the component regardless of the indentation, like C++ namespaces.
+    /// Default is `indent`, or the nesting of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    nesting: Option<Nesting>,
+
+    /// Language of the lexer for finding string literals when parsing
+    ///
+    /// The lines inside multi-line strings, raw strings and template literals are never
+    /// comments or the boundaries of components. The code format also uses the lexer
+    /// to remove the lines that only have comments, like block comments.
+    /// Default is the language of the preset if a preset is used.
+    /// If not set, the lines are not lexed.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "LANGUAGE"))]
+    lexer: Option<String>,
+
+    /// Do not lex the lines for string literals, even if a preset is used. See --lexer
+    #[cfg_attr(feature = "cli", arg(long, conflicts_with = "lexer"))]
+    no_lexer: bool,
+
     /// Format for the output
     #[cfg_attr(feature = "cli", arg(long, short, default_value = "summary"))]
     format: Format,
 
+    /// Output mode
+    #[cfg_attr(feature = "cli", arg(long, short, default_value = "text"))]
+    output: Output,
+
+    /// Placeholder for elided lines
+    ///
+    /// `{lines}` is replaced with the number of elided lines, and `{start}` and `{end}`
+    /// are replaced with the line numbers of the first and last elided lines.
+    /// Default is `...`, or the placeholder of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    ellipsis: Option<String>,
+
+    /// Placeholder for function bodies in the stub format
+    ///
+    /// Default is `...`, or the placeholder of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    stub_body: Option<String>,
+
+    /// Regex for the first line of functions in the stub format
+    ///
+    /// Default is the pattern of the preset if a preset is used.
+    /// If not set, all components without children are considered functions.
+    #[cfg_attr(feature = "cli", arg(long))]
+    function_pattern: Option<String>,
+
+    /// Template for printing each component, instead of --format
+    ///
+    /// Placeholders like `{signature}` are replaced by the parts of the component.
+    /// See the README for the available placeholders and sections.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, conflicts_with_all = ["format", "expand", "max_lines"])
+    )]
+    template: Option<String>,
+
+    /// Read the template from the file. See --template
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "FILE", conflicts_with_all = ["format", "expand", "max_lines", "template"])
+    )]
+    template_file: Option<String>,
+
+    /// Maximum number of lines for each component
+    ///
+    /// The richest format that fits is used instead of --format, from detail to summary with
+    /// fewer levels of children expanded, then comment. If nothing fits, the output is truncated.
+    /// The context and breadcrumb lines printed with the component are counted, and it is an
+    /// error if they leave no lines for the component.
+    #[cfg_attr(
+        feature = "cli",
+        arg(long, value_name = "N", conflicts_with_all = ["format", "expand"])
+    )]
+    max_lines: Option<usize>,
+
+    /// Keep the comments that are not outer or inner comments in the code format
+    #[cfg_attr(feature = "cli", arg(long))]
+    keep_comments: bool,
+
+    /// Markup style of doc comments, translated to Markdown in the markdown format
+    ///
+    /// Default is `markdown` (no translation), or the markup of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    doc_markup: Option<DocMarkup>,
+
+    /// Only print the Nth code example in the examples format, starting from 1
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N"))]
+    example: Option<usize>,
+
+    /// Write all code examples of the found components and their children to separate files
+    /// in the directory, instead of printing the output
+    ///
+    /// The files are named `example-N` with the extension of the language of the code fence,
+    /// or the extension of the input file if the fence has no language.
+    /// The paths of the written files are printed.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "DIR"))]
+    examples_dir: Option<String>,
+
+    /// Language for syntax highlighting in the html output and with --color
+    ///
+    /// Default is the language of the preset if a preset is used,
+    /// or detected from the file extension.
+    /// Also sets the language of the source block in the asciidoc and rst outputs,
+    /// which is otherwise detected from the file extension.
+    #[cfg_attr(feature = "cli", arg(long))]
+    language: Option<String>,
+
+    /// Print line numbers in the html, asciidoc and rst outputs
+    #[cfg_attr(feature = "cli", arg(long))]
+    line_numbers: bool,
+
+    /// Title or caption of the code block in the asciidoc and rst outputs
+    #[cfg_attr(feature = "cli", arg(long))]
+    title: Option<String>,
+
+    /// Use CSS classes instead of inline styles in the html output
+    #[cfg_attr(feature = "cli", arg(long))]
+    css_classes: bool,
+
+    /// Highlight the text output with ANSI colors for the terminal
+    #[cfg_attr(feature = "cli", arg(long))]
+    color: bool,
+
     /// Use a preset configuration
     ///
     /// If both presets and individual options are set,
@@ -86,54 +331,335 @@ pub struct CliArgs {
     /// Print the comments of the parents along with the context (implies --context)
     #[cfg_attr(feature = "cli", arg(long, short = 'C'))]
     context_comments: bool,
+
+    /// Print siblings in context
+    ///
+    /// Print the signature and the closing line of the other components next to the found
+    /// component and its parents, instead of replacing them with the placeholder.
+    /// The comments of the siblings are printed if --context-comments is set (implies --context)
+    #[cfg_attr(feature = "cli", arg(long, short = 's'))]
+    context_siblings: bool,
+
+    /// Only print the closest N parents in the context
+    #[cfg_attr(feature = "cli", arg(long, value_name = "N"))]
+    context_depth: Option<usize>,
+
+    /// Print context as a breadcrumb
+    ///
+    /// Instead of printing the parents around the component, print one comment line
+    /// with the first line of each parent, like `// in: impl Foo > fn bar()` (implies --context)
+    #[cfg_attr(feature = "cli", arg(long, short))]
+    breadcrumb: bool,
+
+    /// Prefix for single line comments printed by the tool, like the breadcrumb
+    ///
+    /// Default is `//`, or the prefix of the preset if a preset is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    comment_prefix: Option<String>,
+
+    /// Expand children of the found component
+    ///
+    /// Each value is a search term that is matched against the children of the found component,
+    /// the same way as the search path, including the members of sections.
+    /// The matched children are printed in detail format,
+    /// and the other children are printed in summary format.
+    #[cfg_attr(feature = "cli", arg(long, short))]
+    expand: Vec<String>,
+
+    /// Render the codump directives in an AsciiDoc or reStructuredText file instead of searching
+    ///
+    /// Each `// codump: ARGS` line in an AsciiDoc (.adoc) file, or `.. codump: ARGS` line
+    /// in a reStructuredText (.rst) file, is replaced with the output of `codump ARGS`
+    /// in a source block. The other lines are printed unchanged.
+    #[cfg_attr(feature = "cli", arg(long, value_name = "FILE", exclusive = true))]
+    pub render: Option<String>,
+}
+
+/// Implementation of CliArgs
+impl CliArgs {
+    /// Get the search path followed by the search paths added with `--and`
+    pub fn search_paths(&self) -> Vec<Vec<String>> {
+        std::iter::once(self.search_path.clone())
+            .chain(self.and.0.iter().cloned())
+            .collect()
+    }
+}
+
+/// Search paths added with `--and`, one for each occurrence of the flag
+///
+/// Each occurrence takes all the values until the next flag, so the values are
+/// grouped by occurrence instead of flattened like the other args.
+#[derive(Debug, Default)]
+pub struct AndSearchPaths(pub Vec<Vec<String>>);
+
+/// Id of the `--and` arg
+#[cfg(feature = "cli")]
+const AND_ID: &str = "and";
+
+/// Add the `--and` arg to the command
+#[cfg(feature = "cli")]
+impl clap::Args for AndSearchPaths {
+    fn augment_args(cmd: clap::Command) -> clap::Command {
+        cmd.arg(
+            clap::Arg::new(AND_ID)
+                .long(AND_ID)
+                .num_args(1..)
+                .action(clap::ArgAction::Append)
+                .value_name("SEARCH_PATH")
+                .help("Another search path to print")
+                .long_help(
+                    "Another search path to print\n\n\
+                     Can be repeated. The components are printed in source order, \
+                     and the shared context is only printed once.",
+                ),
+        )
+    }
+
+    fn augment_args_for_update(cmd: clap::Command) -> clap::Command {
+        Self::augment_args(cmd)
+    }
+}
+
+/// Group the values of `--and` by occurrence
+#[cfg(feature = "cli")]
+impl clap::FromArgMatches for AndSearchPaths {
+    fn from_arg_matches(matches: &clap::ArgMatches) -> Result<Self, clap::Error> {
+        let paths = matches
+            .get_occurrences::<String>(AND_ID)
+            .map(|occurrences| {
+                occurrences
+                    .map(|values| values.cloned().collect())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Self(paths))
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &clap::ArgMatches) -> Result<(), clap::Error> {
+        *self = Self::from_arg_matches(matches)?;
+        Ok(())
+    }
 }
 
 /// Internal config data structure
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     /// Pattern for matching outer comments
     pub outer_comments: CommentPattern,
     /// Pattern for matching inner comments
     pub inner_comments: CommentPattern,
     /// Pattern of lines to ignore
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
     pub ignore_lines: Vec<Regex>,
+    /// Pattern for the first line of attributes between the outer comments and the component
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub attribute_pattern: Option<Regex>,
+    /// If attributes should be hidden, except in the detail and code formats
+    pub hide_attributes: bool,
+    /// If outer comments only count if they end directly above a declaration
+    pub adjacent: bool,
+    /// Pattern for the first line of declarations, used in adjacency mode
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub declaration_pattern: Option<Regex>,
+    /// Pattern for the first line of items, which start components without outer comments
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub item_pattern: Option<Regex>,
+    /// Pattern for section header comments, which start components that group the components after them
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub section_pattern: Option<Regex>,
+    /// Pattern for the start of regions, which are sections that end at the region end pattern
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub region_start_pattern: Option<Regex>,
+    /// Pattern for the end of regions
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub region_end_pattern: Option<Regex>,
+    /// Pattern for the last line of multi-line signatures
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub signature_end: Option<Regex>,
+    /// How to find the children of components
+    pub nesting: Nesting,
+    /// Name of the grammar for finding string literals when parsing, None to not lex the lines
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub lexer: Option<String>,
     /// If context should be included
     pub include_context: bool,
     /// If context should include comments
     pub context_include_comments: bool,
+    /// If context should include the siblings of the components
+    pub context_include_siblings: bool,
+    /// Maximum number of parents to include in the context, None for all parents
+    pub context_depth: Option<usize>,
+    /// If context should be printed as a breadcrumb comment
+    pub context_breadcrumb: bool,
+    /// Prefix for single line comments printed by the tool
+    pub comment_prefix: String,
     /// Format of the output
     pub format: Format,
+    /// Output mode
+    pub output: Output,
+    /// Placeholder for elided lines
+    pub ellipsis: Ellipsis,
+    /// Search terms for children of the found component to expand
+    pub expand: Vec<String>,
+    /// Placeholder for function bodies in the stub format
+    pub stub_body: String,
+    /// Pattern for the first line of functions in the stub format
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub function_pattern: Option<Regex>,
+    /// Template for printing each component, None to use the format
+    pub template: Option<Template>,
+    /// Maximum number of lines for each component, None for no limit
+    ///
+    /// If set, the format is selected to fit the limit
+    pub max_lines: Option<usize>,
+    /// If the code format should keep the comments that are not outer or inner comments
+    pub keep_comments: bool,
+    /// Markup style of doc comments
+    pub doc_markup: DocMarkup,
+    /// The code example to print in the examples format, starting from 1. None for all examples
+    pub example: Option<usize>,
+    /// Directory to write all code examples to, instead of printing the output
+    pub examples_dir: Option<String>,
+    /// Name of the grammar for syntax highlighting, None to detect from the file extension
+    pub language: Option<String>,
+    /// Options for the code block in the html, asciidoc and rst outputs
+    pub block: BlockOptions,
+    /// If the text output should be highlighted with ANSI colors
+    pub color: bool,
 }
 
 impl TryFrom<CliArgs> for Config {
     type Error = String;
 
     fn try_from(args: CliArgs) -> Result<Self, Self::Error> {
-        let (outer_comments, inner_comments) = match args.preset {
-            Some(preset) => {
+        let (mut outer_comments, inner_comments) = match args.preset {
+            Some(ref preset) => {
                 let (mut outer, mut inner) = preset.get_patterns();
                 if let Some(v) = args.outer {
-                    outer.single_line = parse_regex(&v)?;
-                }
-                if let Some(v) = args.outer_start {
-                    outer.multi_start = Some(parse_regex(&v)?);
-                }
-                if let Some(v) = args.outer_end {
-                    outer.multi_end = parse_regex(&v)?;
+                    outer.single_line = Some(parse_regex(&v)?);
                 }
+                outer.multi_line = override_block_patterns(
+                    outer.multi_line,
+                    &args.outer_start,
+                    &args.outer_end,
+                    args.outer_nested.as_ref(),
+                    "outer",
+                )?;
                 if let Some(v) = args.inner {
-                    inner.single_line = parse_regex(&v)?;
-                }
-                if let Some(v) = args.inner_start {
-                    inner.multi_start = Some(parse_regex(&v)?);
-                }
-                if let Some(v) = args.inner_end {
-                    inner.multi_end = parse_regex(&v)?;
+                    inner.single_line = Some(parse_regex(&v)?);
                 }
+                inner.multi_line = override_block_patterns(
+                    inner.multi_line,
+                    &args.inner_start,
+                    &args.inner_end,
+                    args.inner_nested.as_ref(),
+                    "inner",
+                )?;
                 (outer, inner)
             }
             None => parse_comment_pattern_from_args(&args)?,
         };
+        if let Some(v) = &args.transparent {
+            outer_comments.transparent = Some(parse_regex(v)?);
+        }
+        if let Some(v) = &args.trailing {
+            outer_comments.trailing = Some(parse_regex(v)?);
+        }
+        let ellipsis = match (&args.ellipsis, &args.preset) {
+            (Some(template), _) => Ellipsis::new(template),
+            (None, Some(preset)) => preset.get_ellipsis(),
+            (None, None) => Ellipsis::default(),
+        };
+        let attribute_pattern = match (&args.attribute, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => Some(preset.get_attribute_pattern()),
+            (None, None) => None,
+        };
+        let declaration_pattern = match (&args.declaration, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => preset.get_declaration_pattern(),
+            (None, None) => None,
+        };
+        let item_pattern = match (&args.item, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => preset.get_item_pattern(),
+            (None, None) => None,
+        };
+        let section_pattern = match (&args.section, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => Some(preset.get_section_pattern()),
+            (None, None) => None,
+        };
+        let (region_start_pattern, region_end_pattern) =
+            match (&args.region_start, &args.region_end, &args.preset) {
+                (Some(start), Some(end), _) => (Some(parse_regex(start)?), Some(parse_regex(end)?)),
+                (None, None, Some(preset)) => {
+                    let (start, end) = preset.get_region_patterns();
+                    (Some(start), Some(end))
+                }
+                _ => (None, None),
+            };
+        let adjacent = args.adjacent || args.preset.as_ref().is_some_and(Preset::is_adjacent);
+        let signature_end = match (&args.signature_end, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => Some(preset.get_signature_end()),
+            (None, None) => None,
+        };
+        let nesting = match (args.nesting, &args.preset) {
+            (Some(nesting), _) => nesting,
+            (None, Some(preset)) => preset.get_nesting(),
+            (None, None) => Nesting::default(),
+        };
+        let lexer = match (args.lexer, &args.preset) {
+            _ if args.no_lexer => None,
+            (Some(lexer), _) => Some(parse_grammar_name(lexer)?),
+            (None, Some(preset)) => Some(preset.get_language().to_string()),
+            (None, None) => None,
+        };
+        let comment_prefix = match (args.comment_prefix, &args.preset) {
+            (Some(prefix), _) => prefix,
+            (None, Some(preset)) => preset.get_comment_prefix().to_string(),
+            (None, None) => "//".to_string(),
+        };
+        let stub_body = match (args.stub_body, &args.preset) {
+            (Some(stub_body), _) => stub_body,
+            (None, Some(preset)) => preset.get_stub_body().to_string(),
+            (None, None) => "...".to_string(),
+        };
+        let function_pattern = match (&args.function_pattern, &args.preset) {
+            (Some(pattern), _) => Some(parse_regex(pattern)?),
+            (None, Some(preset)) => Some(preset.get_function_pattern()),
+            (None, None) => None,
+        };
+        let doc_markup = match (args.doc_markup, &args.preset) {
+            (Some(doc_markup), _) => doc_markup,
+            (None, Some(preset)) => preset.get_doc_markup(),
+            (None, None) => DocMarkup::default(),
+        };
+        let explicit_language = match args.language {
+            Some(language) => Some(parse_grammar_name(language)?),
+            None => None,
+        };
+        let language = match (&explicit_language, &args.preset) {
+            (Some(language), _) => Some(language.clone()),
+            (None, Some(preset)) => Some(preset.get_language().to_string()),
+            (None, None) => None,
+        };
+        let template = match (args.template, args.template_file) {
+            (Some(template), _) => Some(Template::parse(&template)?),
+            (None, Some(path)) => match std::fs::read_to_string(&path) {
+                Ok(template) => Some(Template::parse(&template)?),
+                Err(e) => return Err(format!("io error while reading template {path}: {e}")),
+            },
+            (None, None) => None,
+        };
+        if args.max_lines == Some(0) {
+            return Err("--max-lines must be at least 1".to_string());
+        }
         let mut ignore_lines = vec![];
         for line in args.ignore {
             ignore_lines.push(parse_regex(&line)?);
@@ -143,26 +669,75 @@ impl TryFrom<CliArgs> for Config {
             outer_comments,
             inner_comments,
             ignore_lines,
-            include_context: args.context || args.context_comments,
+            attribute_pattern,
+            hide_attributes: args.hide_attributes,
+            adjacent,
+            declaration_pattern,
+            item_pattern,
+            section_pattern,
+            region_start_pattern,
+            region_end_pattern,
+            signature_end,
+            nesting,
+            lexer,
+            include_context: args.context
+                || args.context_comments
+                || args.context_siblings
+                || args.breadcrumb,
             context_include_comments: args.context_comments,
+            context_include_siblings: args.context_siblings,
+            context_depth: args.context_depth,
+            context_breadcrumb: args.breadcrumb,
+            comment_prefix,
             format: args.format,
+            output: args.output,
+            ellipsis,
+            expand: args.expand,
+            stub_body,
+            function_pattern,
+            template,
+            max_lines: args.max_lines,
+            keep_comments: args.keep_comments,
+            doc_markup,
+            example: args.example,
+            examples_dir: args.examples_dir,
+            language,
+            block: BlockOptions {
+                line_numbers: args.line_numbers,
+                title: args.title,
+                css_classes: args.css_classes,
+                language: explicit_language,
+            },
+            color: args.color,
         })
     }
 }
 
+const MISSING_PATTERN_ERROR: &str = "Comment pattern missing. Either use a --preset or specify all the --outer* and --inner* arguments. See --help for more.";
+
 fn parse_comment_pattern_from_args(
     args: &CliArgs,
 ) -> Result<(CommentPattern, CommentPattern), String> {
     Ok((
         CommentPattern {
-            single_line: parse_comment_pattern(args.outer.as_ref())?,
-            multi_start: Some(parse_comment_pattern(args.outer_start.as_ref())?),
-            multi_end: parse_comment_pattern(args.outer_end.as_ref())?,
+            single_line: Some(parse_comment_pattern(args.outer.as_ref())?),
+            multi_line: parse_block_patterns(
+                &args.outer_start,
+                &args.outer_end,
+                args.outer_nested.as_ref(),
+            )?,
+            transparent: None,
+            trailing: None,
         },
         CommentPattern {
-            single_line: parse_comment_pattern(args.inner.as_ref())?,
-            multi_start: Some(parse_comment_pattern(args.inner_start.as_ref())?),
-            multi_end: parse_comment_pattern(args.inner_end.as_ref())?,
+            single_line: Some(parse_comment_pattern(args.inner.as_ref())?),
+            multi_line: parse_block_patterns(
+                &args.inner_start,
+                &args.inner_end,
+                args.inner_nested.as_ref(),
+            )?,
+            transparent: None,
+            trailing: None,
         },
     ))
 }
@@ -170,10 +745,98 @@ fn parse_comment_pattern_from_args(
 fn parse_comment_pattern(pattern: Option<&String>) -> Result<Regex, String> {
     match pattern {
         Some(s) => parse_regex(s),
-        None => Err("Comment pattern missing. Either use a --preset or specify all the --outer* and --inner* arguments. See --help for more.".to_string()),
+        None => Err(MISSING_PATTERN_ERROR.to_string()),
+    }
+}
+
+fn parse_block_patterns(
+    starts: &[String],
+    ends: &[String],
+    nested: Option<&String>,
+) -> Result<Vec<BlockPattern>, String> {
+    if starts.is_empty() {
+        return Err(MISSING_PATTERN_ERROR.to_string());
+    }
+    if starts.len() != ends.len() {
+        return Err(format!(
+            "Each multi line comment start regex needs an end regex, but there are {} start regex(es) and {} end regex(es)",
+            starts.len(),
+            ends.len()
+        ));
     }
+    let nested = nested.map(|v| parse_regex(v)).transpose()?;
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(start, end)| {
+            Ok(BlockPattern {
+                start: parse_regex(start)?,
+                end: parse_regex(end)?,
+                nested: nested.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Override the multi line comment patterns of a preset with the args
+///
+/// If only the start or only the end regexes are set, the other side is kept from the preset,
+/// which needs the same number of patterns. `name` is the name of the args, like `outer`.
+fn override_block_patterns(
+    preset: Vec<BlockPattern>,
+    starts: &[String],
+    ends: &[String],
+    nested: Option<&String>,
+    name: &str,
+) -> Result<Vec<BlockPattern>, String> {
+    match (starts.is_empty(), ends.is_empty()) {
+        (true, true) => return Ok(preset),
+        (false, false) => return parse_block_patterns(starts, ends, nested),
+        _ => {}
+    }
+    let replace_start = ends.is_empty();
+    let (given, side) = if replace_start {
+        (starts, "start")
+    } else {
+        (ends, "end")
+    };
+    if given.len() != preset.len() {
+        return Err(format!(
+            "The preset has {} multi line comment pattern(s), but there are {} --{name}-{side} regex(es). Set both --{name}-start and --{name}-end to replace the patterns of the preset",
+            preset.len(),
+            given.len()
+        ));
+    }
+    let nested = nested.map(|v| parse_regex(v)).transpose()?;
+    preset
+        .into_iter()
+        .zip(given)
+        .map(|(mut pattern, regex)| {
+            if replace_start {
+                pattern.start = parse_regex(regex)?;
+            } else {
+                pattern.end = parse_regex(regex)?;
+            }
+            if nested.is_some() {
+                pattern.nested = nested.clone();
+            }
+            Ok(pattern)
+        })
+        .collect()
 }
 
 fn parse_regex(s: &str) -> Result<Regex, String> {
     Regex::new(s).map_err(|_| format!("Invalid regex \"{}\". See --help for more.", s))
 }
+
+/// Check that the name is the name of a bundled grammar
+fn parse_grammar_name(name: String) -> Result<String, String> {
+    if find_grammar(&name).is_none() {
+        let names = GRAMMARS.iter().map(|g| g.name).collect::<Vec<_>>();
+        return Err(format!(
+            "Unknown language \"{name}\". Available languages: {}",
+            names.join(", ")
+        ));
+    }
+    Ok(name)
+}
@@ -1,13 +1,20 @@
 //! Logic for printing output in different formats
 
+use std::io::{self, Write};
+
 #[cfg(feature = "cli")]
 use clap::ValueEnum;
 
-use crate::process::{indent_string, summarize_lines, Component, Context};
+use crate::process::{
+    component_comment_text, find_component_examples, indent_string, render_summary_with_kinds,
+    Component, Context, Ellipsis, SummaryLine,
+};
+use crate::{find_comment_lines, find_grammar, find_string_lines, find_string_ranges, Config};
 
 /// Output format
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Format {
     /// Comments + abbreviated code
     #[default]
@@ -16,70 +23,622 @@ pub enum Format {
     Comment,
     /// Comment + all code
     Detail,
+    /// Code examples in the comments
+    Examples,
+    /// Comments translated to Markdown, with the markup style in the config
+    Markdown,
+    /// Comments + signatures, with the bodies of functions replaced by a placeholder
+    Stub,
+    /// All code without comments
+    Code,
 }
 
-/// Format implementation
-impl Format {
+/// Formatter for the found components
+///
+/// Implement this trait to add an output style, and pass it to [`crate::execute_with_formatter`].
+/// Only [`Formatter::format_component`] needs to be implemented.
+pub trait Formatter {
+    /// Format a component, without the context
+    ///
+    /// The contexts are the parents of the component, in the same order as returned by find_component.
+    /// They are printed around the formatted lines by [`Formatter::format_with_context`].
+    fn format_component(
+        &self,
+        component: &Component,
+        contexts: &[Context],
+        config: &Config,
+    ) -> Vec<String>;
+
+    /// Format a component without the context, with the kind of each line
+    ///
+    /// The kinds are used for styling the output, like the HTML output.
+    /// By default, all lines are [`LineKind::Target`]
+    fn format_component_with_kinds(
+        &self,
+        component: &Component,
+        contexts: &[Context],
+        config: &Config,
+    ) -> Vec<(LineKind, String)> {
+        target_lines(self.format_component(component, contexts, config))
+    }
+
     /// Format a component without context
-    pub fn format(&self, component: &Component) -> Vec<String> {
-        self.format_with_context(component, &[])
+    fn format(&self, component: &Component, config: &Config) -> Vec<String> {
+        self.format_with_context(component, &[], config)
     }
 
     /// Format a component with context
-    pub fn format_with_context(&self, component: &Component, contexts: &[Context]) -> Vec<String> {
-        let mut indent: usize = 0;
-        let mut output = vec![];
-        // add context beginning
-        // skip the first one since that's the file
-        contexts.iter().rev().skip(0).for_each(|context| {
-            context.outer_comments.iter().for_each(|line| {
-                output.push(indent_string(line, indent));
-            });
-            context.begin_body_lines.iter().for_each(|line| {
-                output.push(indent_string(line, indent));
-            });
-            indent += context.indent;
+    fn format_with_context(
+        &self,
+        component: &Component,
+        contexts: &[Context],
+        config: &Config,
+    ) -> Vec<String> {
+        format_context(
+            &self.format_component(component, contexts, config),
+            contexts,
+        )
+    }
+
+    /// Write a component with context to the writer, one line at a time
+    fn write(
+        &self,
+        component: &Component,
+        contexts: &[Context],
+        config: &Config,
+        writer: &mut dyn Write,
+    ) -> io::Result<()> {
+        for line in self.format_with_context(component, contexts, config) {
+            writeln!(writer, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Format implementation
+impl Formatter for Format {
+    fn format_component(
+        &self,
+        component: &Component,
+        contexts: &[Context],
+        config: &Config,
+    ) -> Vec<String> {
+        strip_kinds(self.format_component_with_kinds(component, contexts, config))
+    }
+
+    fn format_component_with_kinds(
+        &self,
+        component: &Component,
+        _contexts: &[Context],
+        config: &Config,
+    ) -> Vec<(LineKind, String)> {
+        match self {
+            Format::Summary => format_summary(component, config),
+            Format::Comment => target_lines(format_comment(component)),
+            Format::Detail => target_lines(format_detail(component)),
+            Format::Examples => target_lines(format_examples(component, config)),
+            Format::Markdown => target_lines(format_markdown(component, config)),
+            Format::Stub => format_stub_with_kinds(component, config),
+            Format::Code => target_lines(format_code(component, config)),
+        }
+    }
+}
+
+/// Mark the lines as lines of a found component
+pub fn target_lines(lines: Vec<String>) -> Vec<(LineKind, String)> {
+    lines
+        .into_iter()
+        .map(|line| (LineKind::Target, line))
+        .collect()
+}
+
+/// Remove the kinds from the lines
+pub fn strip_kinds(lines: Vec<(LineKind, String)>) -> Vec<String> {
+    lines.into_iter().map(|(_, line)| line).collect()
+}
+
+/// Surround the formatted lines of a component with its context
+pub fn format_context(component_lines: &[String], contexts: &[Context]) -> Vec<String> {
+    let mut indent = String::new();
+    let mut output = vec![];
+    // add context beginning
+    contexts.iter().rev().for_each(|context| {
+        context.outer_comments.iter().for_each(|line| {
+            output.push(indent_string(line, &indent));
         });
-        // add component
-        let component_lines = match self {
-            Format::Summary => format_summary(component),
-            Format::Comment => format_comment(component),
-            Format::Detail => format_detail(component),
-        };
+        context.begin_body_lines.iter().for_each(|line| {
+            output.push(indent_string(line, &indent));
+        });
+        indent.push_str(&context.indent_prefix);
+    });
+    // add component
+    component_lines.iter().for_each(|line| {
+        output.push(indent_string(line, &indent));
+    });
 
-        component_lines.iter().for_each(|line| {
-            output.push(indent_string(line, indent));
+    // add context ending
+    contexts.iter().for_each(|context| {
+        indent.truncate(indent.len() - context.indent_prefix.len());
+        context.end_body_lines.iter().for_each(|line| {
+            output.push(indent_string(line, &indent));
         });
+    });
+
+    output
+}
+
+/// Format the context as one breadcrumb comment line
+///
+/// The names of the parents are printed from the outermost to the closest one.
+/// Returns None if there are no named parents.
+pub fn format_breadcrumb(contexts: &[Context], comment_prefix: &str) -> Option<String> {
+    let names = contexts
+        .iter()
+        .rev()
+        .filter(|context| !context.name.is_empty())
+        .map(|context| context.name.as_str())
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return None;
+    }
+    Some(format!("{comment_prefix} in: {}", names.join(" > ")))
+}
+
+/// A formatted component with its context, for merging multiple components
+#[derive(Debug, Clone)]
+pub struct FormattedComponent {
+    /// Formatted lines of the component with the kind of each line, without context
+    pub lines: Vec<(LineKind, String)>,
+    /// Range of line numbers of the component. See [`Component::span`]
+    pub span: (usize, usize),
+    /// Context of the component, in the same order as returned by find_component
+    ///
+    /// Empty if context should not be printed
+    pub contexts: Vec<Context>,
+}
+
+/// Implementation of FormattedComponent
+impl FormattedComponent {
+    /// Get the context at the depth, where depth 0 is the outermost context
+    fn context_at(&self, depth: usize) -> Option<&Context> {
+        let len = self.contexts.len();
+        if depth < len {
+            self.contexts.get(len - 1 - depth)
+        } else {
+            None
+        }
+    }
+}
+
+/// Kind of an output line, used for styling the output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineKind {
+    /// Line of a found component
+    Target,
+    /// Line of the context of a found component
+    Context,
+    /// Placeholder inserted by the tool for elided lines
+    Ellipsis,
+}
+
+/// Format multiple components in source order
+///
+/// The context shared by the components are only printed once. The lines between the components
+/// are replaced by the ellipsis.
+pub fn format_merged(components: &[FormattedComponent], ellipsis: &Ellipsis) -> Vec<String> {
+    strip_kinds(format_merged_with_kinds(components, ellipsis))
+}
+
+/// Format multiple components in source order, with the kind of each line
+///
+/// See [`format_merged`]
+pub fn format_merged_with_kinds(
+    components: &[FormattedComponent],
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    let mut components = components.iter().collect::<Vec<_>>();
+    components.sort_by_key(|c| c.span);
+    format_merged_at_depth(&components, 0, ellipsis)
+}
 
-        // add context ending
-        contexts.iter().skip(0).for_each(|context| {
-            indent -= context.indent;
-            context.end_body_lines.iter().for_each(|line| {
-                output.push(indent_string(line, indent));
+/// Format multiple components that share the context up to the depth
+fn format_merged_at_depth(
+    components: &[&FormattedComponent],
+    depth: usize,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    // group the components by the context or the component itself at the depth
+    let group_span = |c: &FormattedComponent| c.context_at(depth).map_or(c.span, |ctx| ctx.span);
+    let mut groups: Vec<Vec<&FormattedComponent>> = vec![];
+    for component in components {
+        match groups.last_mut() {
+            Some(group) if group_span(group[0]) == group_span(component) => group.push(component),
+            _ => groups.push(vec![component]),
+        }
+    }
+
+    let mut output = vec![];
+    let mut last_end: Option<usize> = None;
+    for group in groups {
+        let (start, end) = group_span(group[0]);
+        if let Some(last_end) = last_end {
+            let elided = (last_end..start).collect::<Vec<_>>();
+            output.extend(
+                ellipsis
+                    .render(&elided, "")
+                    .map(|line| (LineKind::Ellipsis, line)),
+            );
+        }
+        last_end = Some(end);
+        // if one of the components is the group itself, it contains the other ones
+        if let Some(component) = group.iter().find(|c| c.context_at(depth).is_none()) {
+            output.extend(component.lines.iter().cloned());
+            continue;
+        }
+        let first = group[0].context_at(depth).unwrap();
+        let last = group[group.len() - 1].context_at(depth).unwrap();
+        output.extend(
+            first
+                .outer_comments
+                .iter()
+                .map(|line| (LineKind::Context, line.clone())),
+        );
+        output.extend(first.begin_body_lines_with_kinds());
+        format_merged_at_depth(&group, depth + 1, ellipsis)
+            .into_iter()
+            .for_each(|(kind, line)| {
+                output.push((kind, indent_string(&line, &first.indent_prefix)))
             });
-        });
+        output.extend(last.end_body_lines_with_kinds());
+    }
+
+    output
+}
 
-        output
+/// Format a component with some of its children expanded
+///
+/// The component itself is summarized. The children at the `expanded` paths of child indices,
+/// like the ones returned by [`find_child_paths`](crate::process::find_child_paths),
+/// are printed in detail format, and the other children are printed in summary format.
+/// The sections with expanded members are summarized with the members expanded.
+///
+/// If the component has no children, this is the same as the summary format.
+pub fn format_expanded(
+    component: &Component,
+    expanded: &[Vec<usize>],
+    config: &Config,
+) -> Vec<String> {
+    strip_kinds(format_expanded_with_kinds(component, expanded, config))
+}
+
+/// Format a component with some of its children expanded, with the kind of each line
+///
+/// See [`format_expanded`]
+pub fn format_expanded_with_kinds(
+    component: &Component,
+    expanded: &[Vec<usize>],
+    config: &Config,
+) -> Vec<(LineKind, String)> {
+    format_with_children(component, config, false, |i, child| {
+        let members = expanded
+            .iter()
+            .filter(|path| path.len() > 1 && path[0] == i)
+            .map(|path| path[1..].to_vec())
+            .collect::<Vec<_>>();
+        if expanded.iter().any(|path| path[..] == [i]) {
+            target_lines(format_detail(child))
+        } else if !members.is_empty() {
+            format_expanded_with_kinds(child, &members, config)
+        } else {
+            format_summary(child, config)
+        }
+    })
+}
+
+/// Format a component with the richest format that fits in `max_lines` lines
+///
+/// The formats are tried in this order: detail, summary with the children expanded
+/// from all levels down to one level, summary, and comment.
+/// If none of them fits, the comment format (or summary if there are no comments) is
+/// truncated, and the last line is replaced by a marker with the number of truncated lines.
+/// At least one line is printed, even if `max_lines` is 0.
+pub fn format_max_lines(component: &Component, max_lines: usize, config: &Config) -> Vec<String> {
+    strip_kinds(format_max_lines_with_kinds(component, max_lines, config))
+}
+
+/// Format a component with the richest format that fits, with the kind of each line
+///
+/// See [`format_max_lines`]
+pub fn format_max_lines_with_kinds(
+    component: &Component,
+    max_lines: usize,
+    config: &Config,
+) -> Vec<(LineKind, String)> {
+    let max_lines = max_lines.max(1);
+    let mut candidates = vec![target_lines(format_detail(component))];
+    for depth in (1..=tree_depth(component)).rev() {
+        candidates.push(format_with_depth(component, depth, config));
+    }
+    candidates.push(format_summary(component, config));
+    let comment = format_comment(component);
+    if !comment.is_empty() {
+        candidates.push(target_lines(comment));
     }
+    let mut output = candidates.pop().unwrap_or_default();
+    if let Some(fit) = candidates
+        .into_iter()
+        .find(|lines| lines.len() <= max_lines)
+    {
+        return fit;
+    }
+    if output.len() > max_lines {
+        let truncated = output.len() + 1 - max_lines;
+        output.truncate(max_lines - 1);
+        output.push((
+            LineKind::Ellipsis,
+            format!("{} ... {truncated} more lines", config.comment_prefix),
+        ));
+    }
+
+    output
+}
+
+/// Get the number of levels of descendants of a component
+fn tree_depth(component: &Component) -> usize {
+    component
+        .children
+        .iter()
+        .map(|child| tree_depth(child) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Format a component in summary format, with the descendants expanded in summary format
+/// up to `depth` levels
+fn format_with_depth(
+    component: &Component,
+    depth: usize,
+    config: &Config,
+) -> Vec<(LineKind, String)> {
+    if depth == 0 {
+        return format_summary(component, config);
+    }
+    format_with_children(component, config, false, |_, child| {
+        format_with_depth(child, depth - 1, config)
+    })
+}
+
+/// Format a component in summary format, with the children formatted by `format_child`
+///
+/// `format_child` is called with the index and the child.
+/// If `keep_gaps` is true, the lines around the children that are not part of any child,
+/// like fields, are kept instead of replaced by the ellipsis.
+/// If the component has no children, this is the same as the summary format.
+fn format_with_children<F>(
+    component: &Component,
+    config: &Config,
+    keep_gaps: bool,
+    format_child: F,
+) -> Vec<(LineKind, String)>
+where
+    F: Fn(usize, &Component) -> Vec<(LineKind, String)>,
+{
+    if component.children.is_empty() {
+        return format_summary(component, config);
+    }
+    let summary = component.summarize_body(
+        0,
+        component.body_lines.len(),
+        component.inner_comments_range,
+    );
+    if !summary
+        .iter()
+        .any(|line| matches!(line, SummaryLine::Elided(_, _)))
+    {
+        return format_summary(component, config);
+    }
+    let render = |summary: &[SummaryLine]| {
+        render_summary_with_kinds(
+            summary,
+            &component.body_lines,
+            &component.body_line_numbers,
+            &component.indent_prefix,
+            &config.ellipsis,
+        )
+    };
+    // index of the first body line at or after the line number
+    let index_of = |line_number: usize| {
+        component
+            .body_line_numbers
+            .iter()
+            .position(|n| *n >= line_number)
+            .unwrap_or(component.body_line_numbers.len())
+    };
+    let mut output = target_lines(component.outer_comments.clone());
+    output.extend(target_lines(shown_attributes(component, config).to_vec()));
+    let mut children = component.children.iter().enumerate().peekable();
+    for line in summary {
+        let (start, end) = match line {
+            SummaryLine::Elided(start, end)
+                if children
+                    .peek()
+                    .is_some_and(|(_, child)| index_of(child.span.0) < end) =>
+            {
+                (start, end)
+            }
+            line => {
+                output.append(&mut render(&[line]));
+                continue;
+            }
+        };
+        // the children replace the part of the ... block they are in,
+        // and the ... before the first child is always shown.
+        // Empty lines between the children are kept instead of replaced by ...
+        let render_gap = |start: usize, end: usize| {
+            let gap = &component.body_lines[start..end];
+            if keep_gaps {
+                target_lines(gap.to_vec())
+            } else if gap.iter().all(|line| line.trim().is_empty()) {
+                gap.iter()
+                    .map(|_| (LineKind::Target, String::new()))
+                    .collect()
+            } else {
+                render(&[SummaryLine::Elided(start, end)])
+            }
+        };
+        let mut elided_start = start;
+        let mut is_first = true;
+        while let Some((i, child)) = children.next_if(|(_, child)| index_of(child.span.0) < end) {
+            let elided_end = index_of(child.span.0).clamp(elided_start, end);
+            if is_first && !keep_gaps {
+                output.append(&mut render(&[SummaryLine::Elided(
+                    elided_start,
+                    elided_end,
+                )]));
+            } else {
+                output.append(&mut render_gap(elided_start, elided_end));
+            }
+            format_child(i, child).into_iter().for_each(|(kind, line)| {
+                output.push((kind, indent_string(&line, &component.indent_prefix)));
+            });
+            elided_start = index_of(child.span.1).clamp(elided_start, end);
+            is_first = false;
+        }
+        output.append(&mut render_gap(elided_start, end));
+    }
+
+    output
+}
+
+/// Format a component and its descendants as a stub
+///
+/// The bodies of functions are replaced by the stub body in the config, and the comments
+/// and signatures are kept. Functions are components where the first line matches the function
+/// pattern in the config, or components without children if there is no pattern.
+/// Other components without children are printed in detail format, and the lines between
+/// the children, like fields, are kept.
+pub fn format_stub(component: &Component, config: &Config) -> Vec<String> {
+    strip_kinds(format_stub_with_kinds(component, config))
+}
+
+/// Format a component and its descendants as a stub, with the kind of each line
+///
+/// See [`format_stub`]
+fn format_stub_with_kinds(component: &Component, config: &Config) -> Vec<(LineKind, String)> {
+    let is_function = match &config.function_pattern {
+        Some(pattern) => component
+            .body_lines
+            .first()
+            .is_some_and(|line| pattern.is_match(line)),
+        None => component.children.is_empty(),
+    };
+    if is_function {
+        return target_lines(format_stub_function(component, config));
+    }
+    if component.children.is_empty() {
+        return target_lines(format_detail(component));
+    }
+    format_with_children(component, config, true, |_, child| {
+        format_stub_with_kinds(child, config)
+    })
+}
+
+/// Format a function with the body replaced by the stub body
+///
+/// The body is the last indented block. The other indented blocks, like parameters
+/// of multi-line signatures, and the empty lines after the body are kept
+fn format_stub_function(component: &Component, config: &Config) -> Vec<String> {
+    let summary = component.summarize_body(
+        0,
+        component.body_lines.len(),
+        component.inner_comments_range,
+    );
+    let body = summary
+        .iter()
+        .rposition(|line| matches!(line, SummaryLine::Elided(_, _)));
+    let mut output = component.outer_comments.clone();
+    output.extend(shown_attributes(component, config).iter().cloned());
+    for (i, summary_line) in summary.iter().enumerate() {
+        match summary_line {
+            SummaryLine::Line(j) => output.push(component.body_lines[*j].clone()),
+            SummaryLine::Elided(start, end) if Some(i) == body => {
+                output.push(indent_string(&config.stub_body, &component.indent_prefix));
+                // keep the empty lines that separate the function from the next one
+                let trailing_empty = component.body_lines[*start..*end]
+                    .iter()
+                    .rev()
+                    .take_while(|line| line.trim().is_empty())
+                    .count();
+                output.extend(
+                    component.body_lines[*end - trailing_empty..*end]
+                        .iter()
+                        .cloned(),
+                );
+            }
+            SummaryLine::Elided(start, end) => {
+                output.extend(component.body_lines[*start..*end].iter().cloned())
+            }
+        }
+    }
+
+    output
+}
+
+/// Format the code examples in the comments of a component
+///
+/// Only the example selected by `config.example` is printed if it is set.
+/// Otherwise, the examples are separated by an empty line.
+fn format_examples(component: &Component, config: &Config) -> Vec<String> {
+    let examples = find_component_examples(component, config);
+    if let Some(n) = config.example {
+        return match n.checked_sub(1).and_then(|i| examples.get(i)) {
+            Some(example) => example.lines.clone(),
+            None => vec![],
+        };
+    }
+    let mut output = vec![];
+    for example in examples {
+        if !output.is_empty() {
+            output.push(String::new());
+        }
+        output.extend(example.lines);
+    }
+
+    output
+}
+
+/// Format the outer and inner comments of a component as Markdown
+///
+/// The comment markers are removed, and the doc markup is translated to Markdown
+fn format_markdown(component: &Component, config: &Config) -> Vec<String> {
+    config
+        .doc_markup
+        .to_markdown(&component_comment_text(component, config))
 }
 
 /// Format a component in summary format
-fn format_summary(component: &Component) -> Vec<String> {
+fn format_summary(component: &Component, config: &Config) -> Vec<(LineKind, String)> {
     let mut output = vec![];
     // add outer comments
     component.outer_comments.iter().for_each(|line| {
-        output.push(line.clone());
+        output.push((LineKind::Target, line.clone()));
     });
+    output.extend(target_lines(shown_attributes(component, config).to_vec()));
     // add summary
-    output.append(&mut summarize_lines(
-        &component.body_lines,
-        component.indent,
-        component.inner_comments_range,
-    ));
+    output.append(&mut component.summarize_with_kinds(&config.ellipsis));
 
     output
 }
 
+/// Get the attributes of a component to print, which are empty if attributes are hidden
+fn shown_attributes<'a>(component: &'a Component, config: &Config) -> &'a [String] {
+    if config.hide_attributes {
+        &[]
+    } else {
+        &component.attributes
+    }
+}
+
 /// Format a component in comment only format
 fn format_comment(component: &Component) -> Vec<String> {
     let mut output = vec![];
@@ -87,6 +646,10 @@ fn format_comment(component: &Component) -> Vec<String> {
     component.outer_comments.iter().for_each(|line| {
         output.push(line.clone());
     });
+    // add trailing comments
+    component.trailing_comments.iter().for_each(|line| {
+        output.push(line.clone());
+    });
     // add inner comments
     component.inner_comments.iter().for_each(|line| {
         output.push(line.clone());
@@ -95,6 +658,79 @@ fn format_comment(component: &Component) -> Vec<String> {
     output
 }
 
+/// Format the code of a component and its descendants, without the comments
+///
+/// The outer and inner comments are removed at all levels, except for empty lines.
+/// Other lines starting with the comment prefix are also removed, unless `config.keep_comments` is set.
+/// With a lexer, the lines that only have comments, like the lines of `/* ... */`, are also removed,
+/// and the lines inside string literals are kept.
+/// Trailing comments, like `///< the red channel`, are removed from the end of the lines.
+fn format_code(component: &Component, config: &Config) -> Vec<String> {
+    let (comment_lines, string_lines, strings) =
+        match config.lexer.as_deref().and_then(find_grammar) {
+            Some(grammar) if !config.keep_comments => (
+                find_comment_lines(&component.body_lines, grammar),
+                find_string_lines(&component.body_lines, grammar),
+                find_string_ranges(&component.body_lines, grammar),
+            ),
+            _ => (vec![], vec![], vec![]),
+        };
+    let is_comment = |i: usize, line: &str| {
+        // the lines inside string literals are kept, even if they look like comments
+        if config.keep_comments || string_lines.get(i).copied().unwrap_or_default() {
+            return false;
+        }
+        comment_lines.get(i).copied().unwrap_or_default()
+            || !config.comment_prefix.is_empty()
+                && line.trim_start().starts_with(&config.comment_prefix)
+    };
+    // index of the first body line at or after the line number
+    let index_of = |line_number: usize| {
+        component
+            .body_line_numbers
+            .iter()
+            .position(|n| *n >= line_number)
+            .unwrap_or(component.body_line_numbers.len())
+    };
+    // empty lines can be outer comments, like with `--outer '^$'`
+    let mut output = component
+        .outer_comments
+        .iter()
+        .filter(|line| line.trim().is_empty())
+        .chain(component.attributes.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut children = component.children.iter().peekable();
+    let mut i = 0;
+    while i < component.body_lines.len() {
+        if let Some(child) = children.next_if(|child| index_of(child.span.0) <= i) {
+            format_code(child, config).iter().for_each(|line| {
+                output.push(indent_string(line, &component.indent_prefix));
+            });
+            i = index_of(child.span.1).max(i + 1);
+            continue;
+        }
+        let in_inner_comments = component
+            .inner_comments_range
+            .is_some_and(|(start, end)| (start..end).contains(&i));
+        let line = component.body_lines[i].as_str();
+        let line_strings = strings.get(i).map_or(&[][..], Vec::as_slice);
+        // the lines with only trailing comments are removed
+        let code = match config.outer_comments.find_trailing(line, line_strings) {
+            Some(start) if !config.keep_comments => {
+                Some(line[..start].trim_end()).filter(|code| !code.is_empty())
+            }
+            _ => Some(line),
+        };
+        if let Some(code) = code.filter(|code| !in_inner_comments && !is_comment(i, code)) {
+            output.push(code.to_string());
+        }
+        i += 1;
+    }
+
+    output
+}
+
 /// Format a component in detail format
 fn format_detail(component: &Component) -> Vec<String> {
     let mut output = vec![];
@@ -102,6 +738,10 @@ fn format_detail(component: &Component) -> Vec<String> {
     component.outer_comments.iter().for_each(|line| {
         output.push(line.clone());
     });
+    // add attributes
+    component.attributes.iter().for_each(|line| {
+        output.push(line.clone());
+    });
     // add full body, which includes inner comments
     component.body_lines.iter().for_each(|line| {
         output.push(line.clone());
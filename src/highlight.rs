@@ -0,0 +1,647 @@
+//! Lightweight syntax highlighting for the output
+//!
+//! The highlighting is based on simple grammars with keywords, comments and strings,
+//! which is enough for code snippets and does not need to parse the language.
+//! The same grammars are used to find the lines inside string literals when parsing.
+
+use std::ops::Range;
+
+/// A simple grammar for syntax highlighting
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+    /// Name of the grammar, also used as the language name in the output
+    pub name: &'static str,
+    /// File extensions that use this grammar
+    pub extensions: &'static [&'static str],
+    /// Keywords
+    pub keywords: &'static [&'static str],
+    /// Start of single line comments
+    pub line_comments: &'static [&'static str],
+    /// Start and end of block comments
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters. Longer delimiters should be placed first
+    pub string_delimiters: &'static [&'static str],
+    /// Delimiters of the strings that can span multiple lines
+    pub multi_line_strings: &'static [&'static str],
+    /// Syntax of raw strings
+    pub raw_strings: RawStrings,
+    /// If `'` starts a character literal like `'a'`, but not a lifetime or label like `'a`
+    pub char_literals: bool,
+}
+
+/// Syntax of raw strings, which have no escapes and can span multiple lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RawStrings {
+    /// No raw strings
+    None,
+    /// Rust style, like `r#"..."#`, where the number of `#` is the same at the start and end
+    Rust,
+    /// C++ style, like `R"delim(...)delim"`
+    Cpp,
+}
+
+/// Grammars bundled with the tool
+pub const GRAMMARS: &[Grammar] = &[
+    Grammar {
+        name: "rust",
+        extensions: &["rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+            "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+            "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &["\""],
+        multi_line_strings: &["\""],
+        raw_strings: RawStrings::Rust,
+        char_literals: true,
+    },
+    Grammar {
+        name: "clike",
+        extensions: &[
+            "c", "h", "cc", "cpp", "cxx", "hpp", "cs", "java", "js", "jsx", "ts", "tsx", "go",
+            "kt", "swift",
+        ],
+        keywords: &[
+            "abstract",
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "export",
+            "extends",
+            "false",
+            "final",
+            "finally",
+            "for",
+            "func",
+            "function",
+            "if",
+            "implements",
+            "import",
+            "interface",
+            "let",
+            "namespace",
+            "new",
+            "null",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "struct",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "while",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        string_delimiters: &["\"", "'", "`"],
+        multi_line_strings: &["`"],
+        raw_strings: RawStrings::Cpp,
+        char_literals: false,
+    },
+    Grammar {
+        name: "python",
+        extensions: &["py", "pyi"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+            "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return",
+            "self", "True", "try", "while", "with", "yield",
+        ],
+        line_comments: &["#"],
+        block_comment: None,
+        string_delimiters: &["\"\"\"", "'''", "\"", "'"],
+        multi_line_strings: &["\"\"\"", "'''"],
+        raw_strings: RawStrings::None,
+        char_literals: false,
+    },
+];
+
+/// Find a bundled grammar by name
+pub fn find_grammar(name: &str) -> Option<&'static Grammar> {
+    GRAMMARS.iter().find(|grammar| grammar.name == name)
+}
+
+/// Find a bundled grammar by the extension of a file path
+pub fn find_grammar_for_file(path: &str) -> Option<&'static Grammar> {
+    let extension = path.rsplit_once('.')?.1;
+    GRAMMARS
+        .iter()
+        .find(|grammar| grammar.extensions.contains(&extension))
+}
+
+/// Names of languages by file extension, as commonly used by documentation tools
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("java", "java"),
+    ("js", "javascript"),
+    ("jsx", "jsx"),
+    ("ts", "typescript"),
+    ("tsx", "tsx"),
+    ("go", "go"),
+    ("kt", "kotlin"),
+    ("swift", "swift"),
+    ("py", "python"),
+    ("pyi", "python"),
+];
+
+/// Find the name of the language by the extension of a file path
+pub fn find_language_for_file(path: &str) -> Option<&'static str> {
+    let extension = path.rsplit_once('.')?.1;
+    LANGUAGES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, name)| *name)
+}
+
+/// Find the file extension for the name of a language
+///
+/// Extensions are also accepted as names, like `ts` in the info string of a code fence
+pub fn find_extension_for_language(language: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(ext, name)| *name == language || *ext == language)
+        .map(|(ext, _)| *ext)
+}
+
+/// Kind of a highlighted token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    /// Code that is not highlighted
+    Plain,
+    /// Keyword
+    Keyword,
+    /// String literal
+    String,
+    /// Number literal
+    Number,
+    /// Comment
+    Comment,
+}
+
+/// State of the highlighter that carries over to the next line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum HighlightState {
+    /// Not inside any block
+    #[default]
+    Normal,
+    /// Inside a block comment
+    BlockComment,
+    /// Inside a string with the delimiter
+    String(&'static str),
+    /// Inside a raw string with the end delimiter
+    RawString(String),
+}
+
+/// Highlight one line
+///
+/// The state is updated for the next line.
+/// Returns the tokens of the line. Concatenating the tokens gives back the line.
+pub fn highlight_line(
+    line: &str,
+    grammar: &Grammar,
+    state: &mut HighlightState,
+) -> Vec<(TokenKind, String)> {
+    let mut tokens: Vec<(TokenKind, String)> = vec![];
+    let mut push = |kind: TokenKind, s: &str| {
+        if s.is_empty() {
+            return;
+        }
+        match tokens.last_mut() {
+            Some((last_kind, last)) if *last_kind == kind => last.push_str(s),
+            _ => tokens.push((kind, s.to_string())),
+        }
+    };
+    let mut rest = line;
+    while !rest.is_empty() {
+        match state.clone() {
+            HighlightState::BlockComment => {
+                let end = grammar
+                    .block_comment
+                    .map(|(_, end)| end)
+                    .unwrap_or_default();
+                match rest.find(end) {
+                    Some(i) if !end.is_empty() => {
+                        push(TokenKind::Comment, &rest[..i + end.len()]);
+                        rest = &rest[i + end.len()..];
+                        *state = HighlightState::Normal;
+                    }
+                    _ => {
+                        push(TokenKind::Comment, rest);
+                        rest = "";
+                    }
+                }
+            }
+            HighlightState::String(delim) => match find_string_end(rest, delim) {
+                Some(i) => {
+                    push(TokenKind::String, &rest[..i]);
+                    rest = &rest[i..];
+                    *state = HighlightState::Normal;
+                }
+                None => {
+                    push(TokenKind::String, rest);
+                    rest = "";
+                }
+            },
+            HighlightState::RawString(end) => match rest.find(&end) {
+                Some(i) => {
+                    push(TokenKind::String, &rest[..i + end.len()]);
+                    rest = &rest[i + end.len()..];
+                    *state = HighlightState::Normal;
+                }
+                None => {
+                    push(TokenKind::String, rest);
+                    rest = "";
+                }
+            },
+            HighlightState::Normal => {
+                if grammar.line_comments.iter().any(|c| rest.starts_with(c)) {
+                    push(TokenKind::Comment, rest);
+                    rest = "";
+                } else if let Some((start, _)) = grammar
+                    .block_comment
+                    .filter(|(start, _)| rest.starts_with(start))
+                {
+                    push(TokenKind::Comment, start);
+                    rest = &rest[start.len()..];
+                    *state = HighlightState::BlockComment;
+                } else if let Some((start, end)) = find_raw_string_start(rest, grammar.raw_strings)
+                {
+                    push(TokenKind::String, &rest[..start]);
+                    rest = &rest[start..];
+                    *state = HighlightState::RawString(end);
+                } else if let Some(end) = grammar
+                    .char_literals
+                    .then(|| find_char_literal_end(rest))
+                    .flatten()
+                {
+                    push(TokenKind::String, &rest[..end]);
+                    rest = &rest[end..];
+                } else if let Some(delim) = grammar
+                    .string_delimiters
+                    .iter()
+                    .find(|delim| rest.starts_with(*delim))
+                {
+                    push(TokenKind::String, delim);
+                    rest = &rest[delim.len()..];
+                    *state = HighlightState::String(delim);
+                } else {
+                    let c = rest.chars().next().unwrap_or_default();
+                    if c.is_alphanumeric() || c == '_' {
+                        let end = rest
+                            .find(|c: char| !c.is_alphanumeric() && c != '_')
+                            .unwrap_or(rest.len());
+                        let word = &rest[..end];
+                        let kind = if c.is_ascii_digit() {
+                            TokenKind::Number
+                        } else if grammar.keywords.contains(&word) {
+                            TokenKind::Keyword
+                        } else {
+                            TokenKind::Plain
+                        };
+                        push(kind, word);
+                        rest = &rest[end..];
+                    } else {
+                        push(TokenKind::Plain, &rest[..c.len_utf8()]);
+                        rest = &rest[c.len_utf8()..];
+                    }
+                }
+            }
+        }
+    }
+    // single line strings end at the end of the line
+    if let HighlightState::String(delim) = state {
+        if !grammar.multi_line_strings.contains(delim) {
+            *state = HighlightState::Normal;
+        }
+    }
+
+    tokens
+}
+
+/// Find the lines that start inside a string literal
+///
+/// The lines are lexed with the grammar, so quotes in comments are ignored
+pub fn find_string_lines(lines: &[String], grammar: &Grammar) -> Vec<bool> {
+    let mut state = HighlightState::default();
+    lines
+        .iter()
+        .map(|line| {
+            let in_string = matches!(
+                state,
+                HighlightState::String(_) | HighlightState::RawString(_)
+            );
+            highlight_line(line, grammar, &mut state);
+            in_string
+        })
+        .collect()
+}
+
+/// Find the byte ranges of the string literals in each line
+///
+/// The lines are lexed with the grammar like in [`find_string_lines`], so quotes in comments
+/// are ignored. The ranges include the delimiters.
+pub fn find_string_ranges(lines: &[String], grammar: &Grammar) -> Vec<Vec<Range<usize>>> {
+    let mut state = HighlightState::default();
+    lines
+        .iter()
+        .map(|line| {
+            let mut start = 0;
+            highlight_line(line, grammar, &mut state)
+                .into_iter()
+                .filter_map(|(kind, text)| {
+                    let range = start..start + text.len();
+                    start = range.end;
+                    (kind == TokenKind::String).then_some(range)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Find the lines that only have comments
+///
+/// The lines are lexed with the grammar, so the lines inside block comments are found,
+/// and comment markers in strings are ignored. Empty lines are not comment lines.
+pub fn find_comment_lines(lines: &[String], grammar: &Grammar) -> Vec<bool> {
+    let mut state = HighlightState::default();
+    lines
+        .iter()
+        .map(|line| {
+            let tokens = highlight_line(line, grammar, &mut state);
+            let mut code = tokens
+                .iter()
+                .filter(|(_, text)| !text.trim().is_empty())
+                .peekable();
+            code.peek().is_some() && code.all(|(kind, _)| *kind == TokenKind::Comment)
+        })
+        .collect()
+}
+
+/// Find the start of a raw string
+///
+/// Returns the length of the start delimiter and the end delimiter
+fn find_raw_string_start(s: &str, syntax: RawStrings) -> Option<(usize, String)> {
+    match syntax {
+        RawStrings::None => None,
+        RawStrings::Rust => {
+            let rest = s.strip_prefix('b').unwrap_or(s).strip_prefix('r')?;
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            rest[hashes..].strip_prefix('"')?;
+            let start = s.len() - rest.len() + hashes + 1;
+            Some((start, format!("\"{}", "#".repeat(hashes))))
+        }
+        RawStrings::Cpp => {
+            let prefix = ["u8R\"", "uR\"", "UR\"", "LR\"", "R\""]
+                .iter()
+                .find(|prefix| s.starts_with(*prefix))?;
+            let rest = &s[prefix.len()..];
+            let open = rest.find('(')?;
+            let delim = &rest[..open];
+            if delim.len() > 16 || delim.contains(|c: char| c.is_whitespace() || c == '\\') {
+                return None;
+            }
+            Some((prefix.len() + open + 1, format!("){delim}\"")))
+        }
+    }
+}
+
+/// Find the end of a character literal like `'a'` or `'\n'`
+///
+/// Returns None if the `'` is a lifetime or label like `'a`, or is not closed
+fn find_char_literal_end(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\'')?;
+    let mut chars = rest.chars();
+    match (chars.next()?, chars.next()) {
+        ('\\', _) => find_string_end(rest, "'").map(|i| i + 1),
+        (c, Some('\'')) => Some(1 + c.len_utf8() + 1),
+        _ => None,
+    }
+}
+
+/// Find the end of a string, skipping escaped characters
+///
+/// Returns the index after the end delimiter
+fn find_string_end(s: &str, delim: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if s[i..].starts_with(delim) {
+            return Some(i + delim.len());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    fn rust() -> &'static Grammar {
+        find_grammar("rust").unwrap()
+    }
+
+    #[test]
+    fn test_find_grammar_for_file() {
+        assert_eq!(find_grammar_for_file("src/lib.rs").unwrap().name, "rust");
+        assert_eq!(find_grammar_for_file("a/b.test.ts").unwrap().name, "clike");
+        assert!(find_grammar_for_file("Makefile").is_none());
+    }
+
+    #[test]
+    fn test_find_language_for_file() {
+        assert_eq!(find_language_for_file("a/b.test.ts"), Some("typescript"));
+        assert_eq!(find_language_for_file("Makefile"), None);
+    }
+
+    #[test]
+    fn test_keyword_and_string() {
+        let mut state = HighlightState::default();
+        let tokens = highlight_line("let x = \"a\\\"b\"; // hi", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "let".to_string()),
+                (TokenKind::Plain, " x = ".to_string()),
+                (TokenKind::String, "\"a\\\"b\"".to_string()),
+                (TokenKind::Plain, "; ".to_string()),
+                (TokenKind::Comment, "// hi".to_string()),
+            ]
+        );
+        assert_eq!(state, HighlightState::Normal);
+    }
+
+    #[test]
+    fn test_number_and_identifier() {
+        let mut state = HighlightState::default();
+        let tokens = highlight_line("fnx 12", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Plain, "fnx ".to_string()),
+                (TokenKind::Number, "12".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_comment_multi_line() {
+        let mut state = HighlightState::default();
+        let tokens = highlight_line("a /* b", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Plain, "a ".to_string()),
+                (TokenKind::Comment, "/* b".to_string()),
+            ]
+        );
+        assert_eq!(state, HighlightState::BlockComment);
+        let tokens = highlight_line("c */ d", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Comment, "c */".to_string()),
+                (TokenKind::Plain, " d".to_string()),
+            ]
+        );
+        assert_eq!(state, HighlightState::Normal);
+    }
+
+    #[test]
+    fn test_python_docstring() {
+        let python = find_grammar("python").unwrap();
+        let mut state = HighlightState::default();
+        highlight_line("    \"\"\"doc", python, &mut state);
+        assert_eq!(state, HighlightState::String("\"\"\""));
+        let tokens = highlight_line("    end\"\"\" x", python, &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::String, "    end\"\"\"".to_string()),
+                (TokenKind::Plain, " x".to_string()),
+            ]
+        );
+        assert_eq!(state, HighlightState::Normal);
+    }
+
+    #[test]
+    fn test_multi_line_string() {
+        let mut state = HighlightState::default();
+        highlight_line("let s = \"a", rust(), &mut state);
+        assert_eq!(state, HighlightState::String("\""));
+        let clike = find_grammar("clike").unwrap();
+        let mut state = HighlightState::default();
+        highlight_line("let s = \"a", clike, &mut state);
+        assert_eq!(state, HighlightState::Normal);
+    }
+
+    #[test]
+    fn test_raw_string() {
+        let mut state = HighlightState::default();
+        let tokens = highlight_line("x(r#\"a\"b\"#, br\"\\\")", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Plain, "x(".to_string()),
+                (TokenKind::String, "r#\"a\"b\"#".to_string()),
+                (TokenKind::Plain, ", ".to_string()),
+                (TokenKind::String, "br\"\\\"".to_string()),
+                (TokenKind::Plain, ")".to_string()),
+            ]
+        );
+        let clike = find_grammar("clike").unwrap();
+        highlight_line("auto s = R\"sql(", clike, &mut state);
+        assert_eq!(state, HighlightState::RawString(")sql\"".to_string()));
+    }
+
+    #[test]
+    fn test_char_literal() {
+        let mut state = HighlightState::default();
+        let tokens = highlight_line("f::<'a>('\"', '\\'')", rust(), &mut state);
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Plain, "f::<'a>(".to_string()),
+                (TokenKind::String, "'\"'".to_string()),
+                (TokenKind::Plain, ", ".to_string()),
+                (TokenKind::String, "'\\''".to_string()),
+                (TokenKind::Plain, ")".to_string()),
+            ]
+        );
+        assert_eq!(state, HighlightState::Normal);
+    }
+
+    #[test]
+    fn test_string_lines() {
+        let lines = [
+            "let s = \"",
+            "/// not a comment",
+            "\"; // \"",
+            "/* \" */ let c = '\"';",
+            "x",
+        ]
+        .map(|s| s.to_string());
+        assert_eq!(
+            find_string_lines(&lines, rust()),
+            vec![false, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_comment_lines() {
+        let lines = [
+            "/*",
+            "  * comment",
+            "*/",
+            "",
+            "let s = \"/*\"; // comment",
+            "    // comment",
+            "/* a */ let x = 1;",
+        ]
+        .map(|s| s.to_string());
+        assert_eq!(
+            find_comment_lines(&lines, rust()),
+            vec![true, true, true, false, false, true, false]
+        );
+    }
+
+    #[test]
+    fn test_string_ranges() {
+        let lines = ["let s = \"a\" + \"b", "c\"; // \"d\""].map(|s| s.to_string());
+        assert_eq!(
+            find_string_ranges(&lines, rust()),
+            vec![vec![8..11, 14..16], vec![0..2]]
+        );
+    }
+}
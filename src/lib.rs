@@ -4,8 +4,12 @@
 
 use std::fs;
 use std::io;
+use std::path::Path;
 
-use process::{find_component, parse_component, Component, FindComponentResult};
+use process::{
+    find_child_paths, find_component, find_component_examples, parse_component, Component, Context,
+    Example, FindComponentResult,
+};
 
 mod config;
 pub mod presets;
@@ -13,37 +17,324 @@ pub mod process;
 pub use config::*;
 mod format;
 pub use format::*;
+mod output;
+pub use output::*;
+mod highlight;
+pub use highlight::*;
+mod markup;
+pub use markup::*;
+mod template;
+pub use template::*;
+#[cfg(feature = "cli")]
+mod render;
+#[cfg(feature = "cli")]
+pub use render::*;
 
 /// Run the tool
 ///
 /// On success, returns the output of the tool as a vector of lines.
 /// On failure, returns an error message.
 pub fn execute(file: &str, search_path: &[String], config: &Config) -> Result<Vec<String>, String> {
-    let result = match search_file(file, search_path, config) {
-        Ok(result) => result,
+    execute_multiple(file, &[search_path.to_vec()], config)
+}
+
+/// Run the tool with multiple search paths
+///
+/// The found components are printed in source order. If context is included,
+/// the context shared by the components is only printed once.
+///
+/// On success, returns the output of the tool as a vector of lines.
+/// On failure, returns an error message.
+pub fn execute_multiple(
+    file: &str,
+    search_paths: &[Vec<String>],
+    config: &Config,
+) -> Result<Vec<String>, String> {
+    execute_internal(file, search_paths, &config.format, config)
+}
+
+/// Run the tool with multiple search paths and a custom formatter
+///
+/// The formatter is used instead of the format in the config. The other options, like
+/// the context and the output mode, are the same as [`execute_multiple`].
+/// The template, expand and max lines options also select how the components are formatted,
+/// so they cannot be used with a custom formatter.
+///
+/// On success, returns the output of the tool as a vector of lines.
+/// On failure, returns an error message.
+pub fn execute_with_formatter(
+    file: &str,
+    search_paths: &[Vec<String>],
+    formatter: &dyn Formatter,
+    config: &Config,
+) -> Result<Vec<String>, String> {
+    if config.template.is_some() || !config.expand.is_empty() || config.max_lines.is_some() {
+        return Err(
+            "A custom formatter cannot be used with a template, --expand or --max-lines"
+                .to_string(),
+        );
+    }
+    execute_internal(file, search_paths, formatter, config)
+}
+
+/// Run the tool with the formatter for the components that are not formatted by
+/// the template, expand or max lines options
+fn execute_internal(
+    file: &str,
+    search_paths: &[Vec<String>],
+    formatter: &dyn Formatter,
+    config: &Config,
+) -> Result<Vec<String>, String> {
+    let root = match parse_file(file, config) {
+        Ok(root) => root,
         Err(e) => return Err(format!("io error while processing file {}: {}", file, e)),
     };
 
-    match result {
-        FindComponentResult::NotFound(term) => {
-            Err(format!("No component found matching \"{term}\""))
-        }
-        FindComponentResult::Multiple(matched_children, term) => {
-            for matched in matched_children {
-                for line in config.format.format(&matched) {
-                    eprintln!("{}", line);
+    let mut found = vec![];
+    let mut spans: Vec<(&Vec<String>, (usize, usize))> = vec![];
+    let mut results = vec![];
+    let mut examples = vec![];
+    for search_path in search_paths {
+        match find_component(&root, search_path, config) {
+            FindComponentResult::NotFound(term) => {
+                return Err(format!("No component found matching \"{term}\""));
+            }
+            FindComponentResult::Multiple(matched_children, term) => {
+                for matched in matched_children {
+                    for line in formatter.format(&matched, config) {
+                        eprintln!("{}", line);
+                    }
+                }
+                return Err(format!("Multiple components found matching \"{term}\". The matched components are shown above."));
+            }
+            FindComponentResult::Found(component, mut context) => {
+                if config.examples_dir.is_some() {
+                    collect_examples(&component, config, &mut examples);
+                    continue;
                 }
+                if let (Format::Examples, Some(n)) = (&config.format, config.example) {
+                    let count = find_component_examples(&component, config).len();
+                    if n == 0 || n > count {
+                        return Err(format!(
+                            "Example {n} not found. The component has {count} example(s)"
+                        ));
+                    }
+                }
+                let mut lines = if let Some(template) = &config.template {
+                    let depth = config.context_depth.unwrap_or(context.len());
+                    let data = TemplateData {
+                        file,
+                        search_path,
+                        component: &component,
+                        contexts: &context[..depth.min(context.len())],
+                    };
+                    target_lines(template.render(&data, config))
+                } else if !config.expand.is_empty() {
+                    let expanded = find_expanded_children(&component, &config.expand)?;
+                    format_expanded_with_kinds(&component, &expanded, config)
+                } else if let Some(max_lines) = config.max_lines {
+                    let context_lines = context_line_count(&context, config);
+                    if context_lines >= max_lines {
+                        return Err(format!(
+                            "The context of \"{}\" has {context_lines} line(s), which leaves no lines for the component in --max-lines {max_lines}. Use a larger --max-lines, or shorten the context with --context-depth or --breadcrumb",
+                            search_path.join(" > ")
+                        ));
+                    }
+                    format_max_lines_with_kinds(&component, max_lines - context_lines, config)
+                } else {
+                    formatter.format_component_with_kinds(&component, &context, config)
+                };
+                if config.output == Output::Json {
+                    results.push(SearchResult {
+                        search_path: search_path.clone(),
+                        component,
+                        context,
+                        lines: strip_kinds(lines),
+                    });
+                    continue;
+                }
+                // the template prints the context itself
+                if !config.include_context || config.template.is_some() {
+                    context.clear();
+                }
+                if let Some(depth) = config.context_depth {
+                    context.truncate(depth);
+                }
+                if config.context_breadcrumb {
+                    if let Some(line) = format_breadcrumb(&context, &config.comment_prefix) {
+                        lines.insert(0, (LineKind::Context, line));
+                    }
+                    context.clear();
+                }
+                // the merged output cannot print a component inside the lines of another one
+                for (other_path, other_span) in &spans {
+                    let (outer, inner) = if contains_span(*other_span, component.span) {
+                        (*other_path, search_path)
+                    } else if contains_span(component.span, *other_span) {
+                        (search_path, *other_path)
+                    } else {
+                        continue;
+                    };
+                    return Err(format!(
+                        "The component found by \"{}\" is inside the component found by \"{}\". Search for only one of them, or use --expand",
+                        inner.join(" > "),
+                        outer.join(" > ")
+                    ));
+                }
+                spans.push((search_path, component.span));
+                found.push(FormattedComponent {
+                    lines,
+                    span: component.span,
+                    contexts: context,
+                });
             }
-            Err(format!("Multiple components found matching \"{term}\". The matched components are shown above."))
         }
-        FindComponentResult::Found(component, context) => {
-            let output = if config.include_context {
-                config.format.format_with_context(&component, &context)
-            } else {
-                config.format.format(&component)
-            };
-            Ok(output)
+    }
+
+    if let Some(dir) = &config.examples_dir {
+        return match write_examples(file, dir, &examples) {
+            Ok(paths) => Ok(paths),
+            Err(e) => Err(format!("io error while writing examples to {}: {}", dir, e)),
+        };
+    }
+    let grammar = match &config.language {
+        Some(language) => find_grammar(language),
+        None => find_grammar_for_file(file),
+    };
+    match config.output {
+        Output::Text if config.color => Ok(format_ansi(
+            &format_merged_with_kinds(&found, &config.ellipsis),
+            grammar,
+        )),
+        Output::Text => Ok(format_merged(&found, &config.ellipsis)),
+        Output::Html => Ok(format_html(
+            &format_merged_with_kinds(&found, &config.ellipsis),
+            grammar,
+            language_name(file, config),
+            &config.block,
+        )),
+        Output::Asciidoc => Ok(format_asciidoc(
+            &format_merged(&found, &config.ellipsis),
+            language_name(file, config),
+            &config.block,
+        )),
+        Output::Rst => Ok(format_rst(
+            &format_merged(&found, &config.ellipsis),
+            language_name(file, config),
+            &config.block,
+        )),
+        Output::Json => format_json(&JsonOutput {
+            version: JSON_OUTPUT_VERSION,
+            file: file.to_string(),
+            results,
+        }),
+    }
+}
+
+/// Check if the outer span contains the inner span, and they are not the same
+fn contains_span(outer: (usize, usize), inner: (usize, usize)) -> bool {
+    outer != inner && outer.0 <= inner.0 && inner.1 <= outer.1
+}
+
+/// Get the number of context lines printed with a component, including the breadcrumb
+///
+/// The lines are counted as if the component was printed alone, even if the context
+/// is shared with other search paths
+fn context_line_count(context: &[Context], config: &Config) -> usize {
+    if !config.include_context {
+        return 0;
+    }
+    let depth = config.context_depth.unwrap_or(context.len());
+    let context = &context[..depth.min(context.len())];
+    if config.context_breadcrumb {
+        return usize::from(format_breadcrumb(context, &config.comment_prefix).is_some());
+    }
+    context.iter().map(Context::line_count).sum()
+}
+
+/// Get the name of the language of the file for the source blocks in documentation outputs
+///
+/// An explicit `--language` wins. Otherwise the file extension is used before the language
+/// of the preset since it's more specific than the grammar
+fn language_name<'a>(file: &str, config: &'a Config) -> Option<&'a str> {
+    config
+        .block
+        .language
+        .as_deref()
+        .or(find_language_for_file(file))
+        .or(config.language.as_deref())
+}
+
+/// Find the paths of the children to expand
+///
+/// Every search term must match at least one child or member of a section.
+/// All matched children are expanded.
+fn find_expanded_children(
+    component: &Component,
+    expand: &[String],
+) -> Result<Vec<Vec<usize>>, String> {
+    let mut expanded = vec![];
+    for term in expand {
+        let matched = find_child_paths(component, term);
+        if matched.is_empty() {
+            return Err(format!(
+                "No child component found matching \"{term}\" to expand"
+            ));
         }
+        expanded.extend(matched);
+    }
+    Ok(expanded)
+}
+
+/// Collect the code examples of a component and all its children recursively
+fn collect_examples(component: &Component, config: &Config, examples: &mut Vec<Example>) {
+    examples.extend(find_component_examples(component, config));
+    for child in &component.children {
+        collect_examples(child, config, examples);
+    }
+}
+
+/// Write the code examples to separate files in the directory
+///
+/// The files are named `example-N` with the extension of the language of the example.
+/// Returns the paths of the written files.
+fn write_examples(file: &str, dir: &str, examples: &[Example]) -> io::Result<Vec<String>> {
+    fs::create_dir_all(dir)?;
+    let mut paths = vec![];
+    for (i, example) in examples.iter().enumerate() {
+        let extension = example_extension(file, example);
+        // not using Path::join so the printed paths are the same on all platforms
+        let path = format!("{}/example-{}{extension}", dir.trim_end_matches('/'), i + 1);
+        let mut content = example.lines.join("\n");
+        content.push('\n');
+        fs::write(&path, content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Get the extension of the file for a code example, including the `.`
+///
+/// The extension comes from the language tag of the code fence, like `.py` for `python`.
+/// Unknown tags are used as the extension, like `.json`. Examples without a tag,
+/// or with the grammar of the preset as the default language, use the extension of the input file.
+fn example_extension(file: &str, example: &Example) -> String {
+    let extension = match example.language.as_deref() {
+        Some(language) => match find_extension_for_language(language) {
+            Some(extension) => Some(extension.to_string()),
+            None if find_grammar(language).is_some() => None,
+            None => Some(language.to_string()),
+        },
+        None => None,
+    };
+    let extension = extension.or_else(|| {
+        Path::new(file)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+    });
+    match extension {
+        Some(extension) => format!(".{extension}"),
+        None => String::new(),
     }
 }
 
@@ -54,17 +345,25 @@ pub fn search_file(
     config: &Config,
 ) -> io::Result<FindComponentResult> {
     let component = parse_file(file_path, config)?;
-    println!("component: {:#?}", component);
 
     Ok(find_component(&component, search_path, config))
 }
 
 /// Parse a file into a component
 pub fn parse_file(path: &str, config: &Config) -> io::Result<Component> {
-    let file_lines = fs::read_to_string(path)?
+    let file_lines: Vec<String> = fs::read_to_string(path)?
         .lines()
         .map(|s| s.to_string())
         .collect();
+    let line_numbers = (1..=file_lines.len()).collect();
 
-    Ok(parse_component(vec![], file_lines, 0, true, config))
+    Ok(parse_component(
+        vec![],
+        file_lines,
+        line_numbers,
+        1,
+        0,
+        true,
+        config,
+    ))
 }
@@ -12,10 +12,16 @@ fn main() {
 /// Internal entrypoint that returns result
 fn main_internal() -> Result<(), String> {
     let args = codump::CliArgs::parse();
+    if let Some(file) = &args.render {
+        for line in codump::render_file(file)? {
+            println!("{line}");
+        }
+        return Ok(());
+    }
     let file = args.file.clone();
-    let search_path = args.search_path.clone();
+    let search_paths = args.search_paths();
     let config = args.try_into()?;
-    let output = codump::execute(&file, &search_path, &config)?;
+    let output = codump::execute_multiple(&file, &search_paths, &config)?;
 
     for line in output {
         println!("{line}");
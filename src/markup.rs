@@ -0,0 +1,736 @@
+//! Translation of doc comment markup to Markdown
+//!
+//! Each markup style is parsed into a [`DocComment`], which is then printed as Markdown
+//! in the same way for all styles.
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use regex::{Captures, Regex};
+
+/// Markup style of doc comments
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DocMarkup {
+    /// Markdown, printed as-is
+    #[default]
+    Markdown,
+    /// Rustdoc: intra-doc links and the `# Arguments` section
+    Rustdoc,
+    /// Javadoc and JSDoc: `@param`, `@returns`, `@throws` and inline tags like `{@code}`
+    Javadoc,
+    /// C# XML: `<summary>`, `<param>`, `<returns>`, `<exception>` and inline tags like `<c>`
+    Xml,
+    /// Python docstrings in Google or NumPy style: `Args`, `Returns` and `Raises` sections
+    Python,
+}
+
+/// A documented item, like a parameter or an exception
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocItem {
+    /// Name of the item
+    pub name: String,
+    /// Type of the item, if documented
+    pub ty: Option<String>,
+    /// Description of the item
+    pub description: Vec<String>,
+}
+
+/// A doc comment parsed from one of the markup styles
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocComment {
+    /// The main description, in Markdown
+    pub description: Vec<String>,
+    /// Parameters
+    pub params: Vec<DocItem>,
+    /// Description of the return value
+    pub returns: Vec<String>,
+    /// Type of the return value, if documented
+    pub returns_ty: Option<String>,
+    /// Exceptions or errors that can be thrown
+    pub throws: Vec<DocItem>,
+}
+
+/// Implementation of DocMarkup
+impl DocMarkup {
+    /// Translate the text of a doc comment to Markdown
+    ///
+    /// The text should have the comment markers removed. See [`crate::process::comment_text`]
+    pub fn to_markdown(&self, text: &[String]) -> Vec<String> {
+        self.parse(text).to_markdown()
+    }
+
+    /// Parse the text of a doc comment
+    pub fn parse(&self, text: &[String]) -> DocComment {
+        match self {
+            DocMarkup::Markdown => DocComment {
+                description: text.to_vec(),
+                ..Default::default()
+            },
+            DocMarkup::Rustdoc => parse_rustdoc(text),
+            DocMarkup::Javadoc => parse_javadoc(text),
+            DocMarkup::Xml => parse_xml(text),
+            DocMarkup::Python => parse_python(text),
+        }
+    }
+}
+
+/// Implementation of DocComment
+impl DocComment {
+    /// Print the doc comment as Markdown
+    ///
+    /// Parameters are printed as a table, followed by the `Returns` and `Throws` sections.
+    /// The type of the return value is printed before its description
+    pub fn to_markdown(&self) -> Vec<String> {
+        let mut output = trim_empty_lines(&self.description).to_vec();
+        if !self.params.is_empty() {
+            push_heading(&mut output, "Parameters");
+            let has_type = self.params.iter().any(|param| param.ty.is_some());
+            if has_type {
+                output.push("| Name | Type | Description |".to_string());
+                output.push("| --- | --- | --- |".to_string());
+            } else {
+                output.push("| Name | Description |".to_string());
+                output.push("| --- | --- |".to_string());
+            }
+            for param in &self.params {
+                let description = join_lines(&param.description).replace('|', "\\|");
+                match (has_type, &param.ty) {
+                    (true, Some(ty)) => {
+                        output.push(format!("| `{}` | `{ty}` | {description} |", param.name))
+                    }
+                    (true, None) => output.push(format!("| `{}` | | {description} |", param.name)),
+                    _ => output.push(format!("| `{}` | {description} |", param.name)),
+                }
+            }
+        }
+        let returns = trim_empty_lines(&self.returns);
+        if !returns.is_empty() || self.returns_ty.is_some() {
+            push_heading(&mut output, "Returns");
+            match (&self.returns_ty, returns.split_first()) {
+                (Some(ty), Some((first, rest))) => {
+                    output.push(format!("`{ty}` {}", first.trim_start()));
+                    output.extend(rest.iter().cloned());
+                }
+                (Some(ty), None) => output.push(format!("`{ty}`")),
+                (None, _) => output.extend(returns.iter().cloned()),
+            }
+        }
+        if !self.throws.is_empty() {
+            push_heading(&mut output, "Throws");
+            for item in &self.throws {
+                match join_lines(&item.description) {
+                    d if d.is_empty() => output.push(format!("- `{}`", item.name)),
+                    d => output.push(format!("- `{}`: {d}", item.name)),
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// Add a section heading, separated from the previous content by an empty line
+fn push_heading(output: &mut Vec<String>, heading: &str) {
+    if !output.is_empty() {
+        output.push(String::new());
+    }
+    output.push(format!("**{heading}**"));
+    output.push(String::new());
+}
+
+/// Join the lines of a description into one line
+fn join_lines(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Remove the empty lines at the start and end
+fn trim_empty_lines(lines: &[String]) -> &[String] {
+    let start = lines.iter().position(|line| !line.trim().is_empty());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => &lines[start..=end],
+        _ => &[],
+    }
+}
+
+/// Check if a line starts or ends a fenced code block
+fn is_fence(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Get the indentation of a line
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Parse rustdoc
+///
+/// Intra-doc links are replaced by their text, and the `# Arguments` (or `# Parameters`)
+/// and `# Returns` sections are parsed. Other sections are kept in the description.
+fn parse_rustdoc(text: &[String]) -> DocComment {
+    // links are not preceded by a word, unlike indexing like `a[i]`
+    let link = Regex::new(r"(^|[^\w\]])\[(`[^`\]]+`|[A-Za-z_][\w:]*)\](\([^)\s]*\))?").unwrap();
+    let link_definition = Regex::new(r"^\[[^\]]+\]:\s*(\S+)\s*$").unwrap();
+    let param = Regex::new(r"^[*-]\s+`?([\w.]+)`?\s*(?:-|:)\s*(.*)$").unwrap();
+    let mut doc = DocComment::default();
+    let mut section = "";
+    let mut in_fence = false;
+    for line in text {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence || is_fence(line) {
+            match section {
+                "returns" => doc.returns.push(line.clone()),
+                _ => doc.description.push(line.clone()),
+            }
+            continue;
+        }
+        if let Some(caps) = link_definition.captures(line) {
+            if !caps[1].contains("://") {
+                continue;
+            }
+        }
+        let line = link
+            .replace_all(line, |caps: &Captures| match caps.get(3) {
+                Some(target) if target.as_str().contains("://") => caps[0].to_string(),
+                _ => format!("{}{}", &caps[1], &caps[2]),
+            })
+            .to_string();
+        if let Some(heading) = line.strip_prefix("# ") {
+            section = match heading.trim() {
+                "Arguments" | "Parameters" => "params",
+                "Returns" => "returns",
+                _ => "",
+            };
+            if !section.is_empty() {
+                continue;
+            }
+        } else if line.starts_with('#') && !line.starts_with("##") {
+            section = "";
+        }
+        match section {
+            "params" => match param.captures(&line) {
+                Some(caps) => doc.params.push(DocItem {
+                    name: caps[1].to_string(),
+                    ty: None,
+                    description: vec![caps[2].to_string()],
+                }),
+                None => match doc.params.last_mut() {
+                    Some(param) if !line.trim().is_empty() => param.description.push(line),
+                    _ => {}
+                },
+            },
+            "returns" => doc.returns.push(line),
+            _ => doc.description.push(line),
+        }
+    }
+
+    doc
+}
+
+/// Parse Javadoc or JSDoc
+///
+/// Block tags must be at the start of a line. Lines after a block tag are part of the tag
+/// until the next block tag. Unknown block tags are kept in the description.
+fn parse_javadoc(text: &[String]) -> DocComment {
+    let code = Regex::new(r"\{@(?:code|literal)\s+([^}]*)\}").unwrap();
+    let link = Regex::new(r"\{@link(?:plain)?\s+([^}\s]+)\s*([^}]*)\}").unwrap();
+    let tag = Regex::new(r"^@(\w+)\s*(?:\{([^}]*)\}\s*)?(.*)$").unwrap();
+    let mut doc = DocComment::default();
+    // the tag the current line belongs to
+    let mut current = "";
+    let mut in_fence = false;
+    for line in text {
+        if is_fence(line) {
+            in_fence = !in_fence;
+        }
+        let line = if in_fence || is_fence(line) {
+            line.clone()
+        } else {
+            let line = code.replace_all(line, "`$1`");
+            link.replace_all(&line, |caps: &Captures| match caps[2].trim() {
+                "" => format!("`{}`", &caps[1]),
+                label => label.to_string(),
+            })
+            .replace("<p>", "")
+            .replace("</p>", "")
+        };
+        let trimmed = line.trim_start();
+        let caps = match tag.captures(trimmed).filter(|_| !in_fence) {
+            Some(caps) => caps,
+            None => {
+                match current {
+                    "param" => doc.params.last_mut().unwrap().description.push(line),
+                    "returns" => doc.returns.push(line),
+                    "throws" => doc.throws.last_mut().unwrap().description.push(line),
+                    _ => doc.description.push(line),
+                }
+                continue;
+            }
+        };
+        let ty = caps.get(2).map(|ty| ty.as_str().to_string());
+        let rest = &caps[3];
+        current = match &caps[1] {
+            "param" | "arg" | "argument" => {
+                let (name, description) =
+                    rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let name = name.trim_matches(|c| c == '[' || c == ']');
+                // JSDoc default values like [name=1]
+                let name = name.split('=').next().unwrap_or_default();
+                let description = description.trim_start().trim_start_matches("- ");
+                doc.params.push(DocItem {
+                    name: name.to_string(),
+                    ty,
+                    description: vec![description.to_string()],
+                });
+                "param"
+            }
+            "return" | "returns" => {
+                doc.returns_ty = ty;
+                doc.returns.push(rest.to_string());
+                "returns"
+            }
+            "throws" | "exception" => {
+                let (name, description) = match ty {
+                    Some(ty) => (ty, rest.to_string()),
+                    None => match rest.split_once(char::is_whitespace) {
+                        Some((name, description)) => (name.to_string(), description.to_string()),
+                        None => (rest.to_string(), String::new()),
+                    },
+                };
+                doc.throws.push(DocItem {
+                    name,
+                    ty: None,
+                    description: vec![description],
+                });
+                "throws"
+            }
+            _ => {
+                doc.description.push(line);
+                ""
+            }
+        };
+    }
+
+    doc
+}
+
+/// Parse C# XML doc comments
+///
+/// `<summary>` and `<remarks>` are used as the description. Text outside of the tags is ignored
+fn parse_xml(text: &[String]) -> DocComment {
+    let text = text.join("\n");
+    let element = |name: &str| {
+        Regex::new(&format!(
+            r#"(?s)<{name}(?:\s+\w+="([^"]*)")?\s*>(.*?)</{name}>"#
+        ))
+        .unwrap()
+    };
+    let to_lines = |s: &str| -> Vec<String> {
+        let inline = translate_xml_inline(s);
+        let lines = inline
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect::<Vec<_>>();
+        trim_empty_lines(&lines).to_vec()
+    };
+    let mut doc = DocComment::default();
+    for name in ["summary", "remarks"] {
+        for caps in element(name).captures_iter(&text) {
+            if !doc.description.is_empty() {
+                doc.description.push(String::new());
+            }
+            doc.description.extend(to_lines(&caps[2]));
+        }
+    }
+    for caps in element("param").captures_iter(&text) {
+        doc.params.push(DocItem {
+            name: caps.get(1).map_or("", |m| m.as_str()).to_string(),
+            ty: None,
+            description: to_lines(&caps[2]),
+        });
+    }
+    for caps in element("returns").captures_iter(&text) {
+        doc.returns.extend(to_lines(&caps[2]));
+    }
+    for caps in element("exception").captures_iter(&text) {
+        doc.throws.push(DocItem {
+            name: caps.get(1).map_or("", |m| m.as_str()).to_string(),
+            ty: None,
+            description: to_lines(&caps[2]),
+        });
+    }
+
+    doc
+}
+
+/// Translate inline XML tags like `<c>` and `<see cref="..."/>` to Markdown
+fn translate_xml_inline(s: &str) -> String {
+    let code = Regex::new(r"(?s)<c>(.*?)</c>").unwrap();
+    let reference =
+        Regex::new(r#"<(?:see|seealso|paramref|typeparamref)\s+\w+="([^"]*)"\s*/>"#).unwrap();
+    let para = Regex::new(r"</?para>").unwrap();
+    let s = code.replace_all(s, "`$1`");
+    let s = reference.replace_all(&s, "`$1`");
+    para.replace_all(&s, "\n").to_string()
+}
+
+/// Parse Python docstrings in Google or NumPy style
+///
+/// Google style sections are a header like `Args:` followed by indented items.
+/// NumPy style sections are a header underlined by `-` followed by items.
+/// Unknown sections are kept in the description.
+///
+/// The type of the return value is the `type:` before the description in Google style,
+/// or the item in NumPy style. NumPy sections with more than one item are kept as a list.
+fn parse_python(text: &[String]) -> DocComment {
+    let underline = Regex::new(r"^\s*-{3,}\s*$").unwrap();
+    let google_return = Regex::new(r"^([\w.]+(?:\[[^\]]*\])?)\s*:\s*(.*)$").unwrap();
+    let section_of = |header: &str| match header.trim().trim_end_matches(':') {
+        "Args" | "Arguments" | "Parameters" | "Params" | "Keyword Args" | "Keyword Arguments"
+        | "Other Parameters" => Some("params"),
+        "Returns" | "Return" | "Yields" | "Yield" => Some("returns"),
+        "Raises" | "Raise" | "Throws" => Some("throws"),
+        _ => None,
+    };
+    let mut doc = DocComment::default();
+    let mut i = 0;
+    while i < text.len() {
+        let line = &text[i];
+        let numpy = text.get(i + 1).is_some_and(|next| underline.is_match(next));
+        let google = line.trim_end().ends_with(':');
+        let section = match section_of(line) {
+            Some(section) if numpy || google => section,
+            _ => {
+                doc.description.push(line.clone());
+                i += 1;
+                continue;
+            }
+        };
+        let header_indent = indent_of(line);
+        i += if numpy { 2 } else { 1 };
+        // collect the lines of the section
+        let start = i;
+        while i < text.len() {
+            let line = &text[i];
+            let is_next_section = section_of(line).is_some()
+                && (line.trim_end().ends_with(':')
+                    || text.get(i + 1).is_some_and(|next| underline.is_match(next)));
+            if is_next_section && indent_of(line) <= header_indent {
+                break;
+            }
+            // google sections end at the first line that is not indented more than the header
+            if !numpy && !line.trim().is_empty() && indent_of(line) <= header_indent {
+                break;
+            }
+            i += 1;
+        }
+        let lines = &text[start..i];
+        match section {
+            "returns" if numpy => {
+                let items = parse_python_items(lines, numpy);
+                match items.as_slice() {
+                    [item] => {
+                        doc.returns_ty = Some(item.ty.clone().unwrap_or_else(|| item.name.clone()));
+                        doc.returns.extend(item.description.iter().cloned());
+                    }
+                    _ => doc.returns.extend(items.iter().map(|item| {
+                        let description = join_lines(&item.description);
+                        let line = match &item.ty {
+                            Some(ty) => format!("- `{}`: `{ty}` {description}", item.name),
+                            None => format!("- `{}` {description}", item.name),
+                        };
+                        line.trim_end().to_string()
+                    })),
+                }
+            }
+            "returns" => {
+                let mut lines = lines.iter().map(|line| line.trim().to_string());
+                let first = lines.next().unwrap_or_default();
+                match google_return.captures(&first) {
+                    Some(caps) => {
+                        doc.returns_ty = Some(caps[1].to_string());
+                        doc.returns.push(caps[2].to_string());
+                    }
+                    None => doc.returns.push(first),
+                }
+                doc.returns.extend(lines);
+            }
+            "params" => doc.params.extend(parse_python_items(lines, numpy)),
+            _ => doc.throws.extend(parse_python_items(lines, numpy)),
+        }
+    }
+
+    doc
+}
+
+/// Parse the items in a Python docstring section
+///
+/// Google style: `name (type): description`. NumPy style: `name : type` followed by
+/// the description on indented lines. Lines indented more than the item are part of the description.
+fn parse_python_items(lines: &[String], numpy: bool) -> Vec<DocItem> {
+    let google_item = Regex::new(r"^([\w*]+)\s*(?:\(([^)]*)\))?\s*:\s*(.*)$").unwrap();
+    let numpy_item = Regex::new(r"^([\w*]+)\s*(?::\s*(.*))?$").unwrap();
+    let item_indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent_of(line))
+        .min()
+        .unwrap_or(0);
+    let mut items: Vec<DocItem> = vec![];
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let trimmed = line.trim();
+        if indent_of(line) == item_indent {
+            let item = if numpy {
+                numpy_item.captures(trimmed).map(|caps| DocItem {
+                    name: caps[1].to_string(),
+                    ty: caps.get(2).map(|ty| ty.as_str().trim().to_string()),
+                    description: vec![],
+                })
+            } else {
+                google_item.captures(trimmed).map(|caps| DocItem {
+                    name: caps[1].to_string(),
+                    ty: caps.get(2).map(|ty| ty.as_str().trim().to_string()),
+                    description: vec![caps[3].to_string()],
+                })
+            };
+            // NumPy exceptions have no name, only the type
+            let item = item.unwrap_or_else(|| DocItem {
+                name: trimmed.to_string(),
+                ..Default::default()
+            });
+            items.push(item);
+        } else if let Some(item) = items.last_mut() {
+            item.description.push(trimmed.to_string());
+        }
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    fn to_lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn item(name: &str, ty: Option<&str>, description: &str) -> DocItem {
+        DocItem {
+            name: name.to_string(),
+            ty: ty.map(|s| s.to_string()),
+            description: vec![description.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let doc = DocComment {
+            description: to_lines(&["", "Add numbers", ""]),
+            params: vec![
+                item("a", None, "first | number"),
+                item("b", Some("int"), "second"),
+            ],
+            returns: to_lines(&["the sum"]),
+            returns_ty: Some("int".to_string()),
+            throws: vec![item("Error", None, "")],
+        };
+        assert_eq!(
+            doc.to_markdown(),
+            to_lines(&[
+                "Add numbers",
+                "",
+                "**Parameters**",
+                "",
+                "| Name | Type | Description |",
+                "| --- | --- | --- |",
+                "| `a` | | first \\| number |",
+                "| `b` | `int` | second |",
+                "",
+                "**Returns**",
+                "",
+                "`int` the sum",
+                "",
+                "**Throws**",
+                "",
+                "- `Error`",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rustdoc() {
+        let text = to_lines(&[
+            "Format with [`Format`] and [Config], see [docs](https://x.y)",
+            "",
+            "# Arguments",
+            "* `a` - first",
+            "  continued",
+            "- b: second",
+            "",
+            "# Examples",
+            "Index with a[i]",
+            "```",
+            "# let v = [Config];",
+            "```",
+            "[Config]: crate::Config",
+        ]);
+        assert_eq!(
+            parse_rustdoc(&text),
+            DocComment {
+                description: to_lines(&[
+                    "Format with `Format` and Config, see [docs](https://x.y)",
+                    "",
+                    "# Examples",
+                    "Index with a[i]",
+                    "```",
+                    "# let v = [Config];",
+                    "```",
+                ]),
+                params: vec![
+                    DocItem {
+                        name: "a".to_string(),
+                        ty: None,
+                        description: to_lines(&["first", "  continued"]),
+                    },
+                    item("b", None, "second"),
+                ],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_javadoc() {
+        let text = to_lines(&[
+            "Add {@code a} and {@link Math#add}",
+            "@param {number} a first",
+            "  continued",
+            "@param [b=1] - second",
+            "@returns {number} the sum",
+            "@throws {RangeError} if too large",
+            "@since 1.0",
+        ]);
+        assert_eq!(
+            parse_javadoc(&text),
+            DocComment {
+                description: to_lines(&["Add `a` and `Math#add`", "@since 1.0"]),
+                params: vec![
+                    DocItem {
+                        name: "a".to_string(),
+                        ty: Some("number".to_string()),
+                        description: to_lines(&["first", "  continued"]),
+                    },
+                    item("b", None, "second"),
+                ],
+                returns: to_lines(&["the sum"]),
+                returns_ty: Some("number".to_string()),
+                throws: vec![item("RangeError", None, "if too large")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_xml() {
+        let text = to_lines(&[
+            "<summary>",
+            "Add <paramref name=\"a\"/> and <c>b</c>",
+            "</summary>",
+            "<param name=\"a\">first</param>",
+            "<returns>the sum</returns>",
+            "<exception cref=\"OverflowException\">if too large</exception>",
+        ]);
+        assert_eq!(
+            parse_xml(&text),
+            DocComment {
+                description: to_lines(&["Add `a` and `b`"]),
+                params: vec![item("a", None, "first")],
+                returns: to_lines(&["the sum"]),
+                returns_ty: None,
+                throws: vec![item("OverflowException", None, "if too large")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_python_google() {
+        let text = to_lines(&[
+            "Add numbers",
+            "",
+            "Args:",
+            "    a (int): first",
+            "        continued",
+            "    b: second",
+            "",
+            "Returns:",
+            "    int: the sum",
+            "Raises:",
+            "    ValueError: if too large",
+            "Note:",
+            "    kept",
+        ]);
+        assert_eq!(
+            parse_python(&text),
+            DocComment {
+                description: to_lines(&["Add numbers", "", "Note:", "    kept"]),
+                params: vec![
+                    DocItem {
+                        name: "a".to_string(),
+                        ty: Some("int".to_string()),
+                        description: to_lines(&["first", "continued"]),
+                    },
+                    item("b", None, "second"),
+                ],
+                returns: to_lines(&["the sum"]),
+                returns_ty: Some("int".to_string()),
+                throws: vec![item("ValueError", None, "if too large")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_python_numpy() {
+        let text = to_lines(&[
+            "Add numbers",
+            "",
+            "Parameters",
+            "----------",
+            "a : int",
+            "    first",
+            "b",
+            "    second",
+            "",
+            "Returns",
+            "-------",
+            "int",
+            "    the sum",
+            "",
+            "Raises",
+            "------",
+            "ValueError",
+            "    if too large",
+        ]);
+        assert_eq!(
+            parse_python(&text),
+            DocComment {
+                description: to_lines(&["Add numbers", ""]),
+                params: vec![item("a", Some("int"), "first"), item("b", None, "second")],
+                returns: to_lines(&["the sum"]),
+                returns_ty: Some("int".to_string()),
+                throws: vec![item("ValueError", None, "if too large")],
+            }
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Logic for writing the output in different modes
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+use crate::highlight::{highlight_line, Grammar, HighlightState, TokenKind};
+use crate::process::{Component, Context};
+use crate::LineKind;
+
+/// Output mode
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Output {
+    /// The formatted lines as plain text
+    #[default]
+    Text,
+    /// The parsed components and contexts as JSON. Requires the `json` feature
+    Json,
+    /// The formatted lines as HTML with syntax highlighting
+    Html,
+    /// The formatted lines in an AsciiDoc source block
+    Asciidoc,
+    /// The formatted lines in a reStructuredText code-block directive
+    Rst,
+}
+
+/// Version of the structure of the JSON output
+///
+/// This is increased when the structure changes in a way that is not backward compatible.
+/// The structure is described by the JSON Schema in `schema/output.schema.json`
+pub const JSON_OUTPUT_VERSION: u32 = 1;
+
+/// Root object of the JSON output
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonOutput {
+    /// Version of the structure. See [`JSON_OUTPUT_VERSION`]
+    pub version: u32,
+    /// The input file
+    pub file: String,
+    /// The results of each search path, in the same order as the search paths
+    pub results: Vec<SearchResult>,
+}
+
+/// A component found with a search path
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchResult {
+    /// The search path that resolved to the component
+    pub search_path: Vec<String>,
+    /// The found component
+    pub component: Component,
+    /// The context of the component
+    ///
+    /// The first element is the parent of the component, and the last element is the file
+    pub context: Vec<Context>,
+    /// The component formatted with the format in the config, without context
+    pub lines: Vec<String>,
+}
+
+/// Render the JSON output as lines
+#[cfg(feature = "json")]
+pub fn format_json(output: &JsonOutput) -> Result<Vec<String>, String> {
+    match serde_json::to_string_pretty(output) {
+        Ok(json) => Ok(json.lines().map(|s| s.to_string()).collect()),
+        Err(e) => Err(format!("Failed to serialize JSON output: {e}")),
+    }
+}
+
+/// Render the JSON output as lines
+///
+/// Always fails since the `json` feature is not enabled
+#[cfg(not(feature = "json"))]
+pub fn format_json(_output: &JsonOutput) -> Result<Vec<String>, String> {
+    Err("JSON output requires the `json` feature".to_string())
+}
+
+/// Options for the outputs that render a code block (HTML, AsciiDoc and reStructuredText)
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockOptions {
+    /// If line numbers should be printed
+    pub line_numbers: bool,
+    /// Title or caption of the block. Not used in the HTML output
+    pub title: Option<String>,
+    /// If CSS classes should be used instead of inline styles
+    ///
+    /// The classes are `cd-line` and `cd-target`/`cd-context`/`cd-ellipsis` on each line,
+    /// `cd-ln` on line numbers, and `cd-keyword`/`cd-string`/`cd-number`/`cd-comment` on tokens
+    pub css_classes: bool,
+    /// Language of the source block in the asciidoc and rst outputs
+    ///
+    /// Overrides the language detected from the file extension
+    pub language: Option<String>,
+}
+
+/// Render the lines as HTML
+///
+/// The output is a `<pre>` block with a `<code>` element. The lines are highlighted with the grammar,
+/// or escaped without highlighting if there is no grammar.
+/// The class of the `<code>` element has the language, or `text` if there is no language.
+pub fn format_html(
+    lines: &[(LineKind, String)],
+    grammar: Option<&Grammar>,
+    language: Option<&str>,
+    options: &BlockOptions,
+) -> Vec<String> {
+    let language = language.unwrap_or("text");
+    let width = lines.len().to_string().len();
+    let mut state = HighlightState::default();
+    let mut output = lines
+        .iter()
+        .enumerate()
+        .map(|(i, (kind, line))| {
+            let mut html = String::new();
+            if options.line_numbers {
+                let number = format!("{:>width$} ", i + 1);
+                html.push_str(&html_span("cd-ln", "color:#888888", &number, options));
+            }
+            match (kind, grammar) {
+                (LineKind::Ellipsis, _) | (_, None) => {
+                    // placeholders are not code, so the highlighting starts over after them
+                    state = HighlightState::default();
+                    html.push_str(&escape_html(line));
+                }
+                (_, Some(grammar)) => {
+                    for (token, text) in highlight_line(line, grammar, &mut state) {
+                        match html_token_style(token) {
+                            Some((class, style)) => html.push_str(&html_span(
+                                class,
+                                style,
+                                &escape_html(&text),
+                                options,
+                            )),
+                            None => html.push_str(&escape_html(&text)),
+                        }
+                    }
+                }
+            }
+            let (class, style) = match kind {
+                LineKind::Target => ("cd-line cd-target", ""),
+                LineKind::Context => ("cd-line cd-context", "opacity:0.6"),
+                LineKind::Ellipsis => ("cd-line cd-ellipsis", "color:#888888;font-style:italic"),
+            };
+            html_span(class, style, &html, options)
+        })
+        .collect::<Vec<_>>();
+
+    let start = format!("<pre class=\"codump\"><code class=\"language-{language}\">");
+    let end = "</code></pre>";
+    match output.first_mut() {
+        Some(first) => first.insert_str(0, &start),
+        None => output.push(start),
+    }
+    if let Some(last) = output.last_mut() {
+        last.push_str(end);
+    }
+    output
+}
+
+/// Render the lines as an AsciiDoc source block
+///
+/// The delimiter is made longer than any line of dashes in the code, so the block is not closed early
+pub fn format_asciidoc(
+    lines: &[String],
+    language: Option<&str>,
+    options: &BlockOptions,
+) -> Vec<String> {
+    let lines = trim_empty_lines(lines);
+    let mut output = vec![];
+    if let Some(title) = &options.title {
+        output.push(format!(".{title}"));
+    }
+    let mut attributes = vec!["source"];
+    attributes.extend(language);
+    if options.line_numbers {
+        attributes.push("linenums");
+    }
+    output.push(format!("[{}]", attributes.join(",")));
+    let delimiter_len = lines
+        .iter()
+        .filter(|line| !line.is_empty() && line.chars().all(|c| c == '-'))
+        .map(|line| line.len() + 1)
+        .fold(4, usize::max);
+    let delimiter = "-".repeat(delimiter_len);
+    output.push(delimiter.clone());
+    output.extend(lines.iter().cloned());
+    output.push(delimiter);
+    output
+}
+
+/// Render the lines as a reStructuredText code-block directive
+///
+/// The lines are indented by 3 spaces to line up with the directive. Lines with only
+/// whitespace are printed as empty lines
+pub fn format_rst(lines: &[String], language: Option<&str>, options: &BlockOptions) -> Vec<String> {
+    let lines = trim_empty_lines(lines);
+    let mut output = vec![format!(".. code-block:: {}", language.unwrap_or("text"))];
+    if let Some(title) = &options.title {
+        output.push(format!("   :caption: {title}"));
+    }
+    if options.line_numbers {
+        output.push("   :linenos:".to_string());
+    }
+    output.push(String::new());
+    output.extend(lines.iter().map(|line| {
+        if line.trim().is_empty() {
+            String::new()
+        } else {
+            format!("   {line}")
+        }
+    }));
+    output
+}
+
+/// Render the lines with ANSI colors for the terminal
+///
+/// The context lines are dimmed, and the placeholders are dimmed and italic
+pub fn format_ansi(lines: &[(LineKind, String)], grammar: Option<&Grammar>) -> Vec<String> {
+    let mut state = HighlightState::default();
+    lines
+        .iter()
+        .map(|(kind, line)| {
+            let grammar = match (kind, grammar) {
+                (LineKind::Ellipsis, _) => {
+                    state = HighlightState::default();
+                    return format!("\x1b[2;3m{line}\x1b[0m");
+                }
+                (_, None) => return line.clone(),
+                (_, Some(grammar)) => grammar,
+            };
+            let mut output = String::new();
+            if *kind == LineKind::Context {
+                output.push_str("\x1b[2m");
+            }
+            for (token, text) in highlight_line(line, grammar, &mut state) {
+                match ansi_token_color(token) {
+                    // only reset the foreground color so the line style is kept
+                    Some(color) => output.push_str(&format!("\x1b[{color}m{text}\x1b[39m")),
+                    None => output.push_str(&text),
+                }
+            }
+            if *kind == LineKind::Context {
+                output.push_str("\x1b[0m");
+            }
+            output
+        })
+        .collect()
+}
+
+/// Get the CSS class and inline style of a token
+fn html_token_style(token: TokenKind) -> Option<(&'static str, &'static str)> {
+    match token {
+        TokenKind::Plain => None,
+        TokenKind::Keyword => Some(("cd-keyword", "color:#0000ff")),
+        TokenKind::String => Some(("cd-string", "color:#a31515")),
+        TokenKind::Number => Some(("cd-number", "color:#098658")),
+        TokenKind::Comment => Some(("cd-comment", "color:#008000")),
+    }
+}
+
+/// Get the ANSI foreground color code of a token
+fn ansi_token_color(token: TokenKind) -> Option<u8> {
+    match token {
+        TokenKind::Plain => None,
+        TokenKind::Keyword => Some(34),
+        TokenKind::String => Some(32),
+        TokenKind::Number => Some(35),
+        TokenKind::Comment => Some(90),
+    }
+}
+
+/// Wrap the HTML in a span with either the class or the inline style
+///
+/// Returns the HTML as is if the style is empty and classes are not used
+fn html_span(class: &str, style: &str, html: &str, options: &BlockOptions) -> String {
+    if options.css_classes {
+        format!("<span class=\"{class}\">{html}</span>")
+    } else if style.is_empty() {
+        html.to_string()
+    } else {
+        format!("<span style=\"{style}\">{html}</span>")
+    }
+}
+
+/// Remove the empty lines at the start and end, which are not needed in a code block
+pub(crate) fn trim_empty_lines(lines: &[String]) -> &[String] {
+    let is_empty = |line: &String| line.trim().is_empty();
+    let start = lines.iter().position(|line| !is_empty(line));
+    let end = lines.iter().rposition(|line| !is_empty(line));
+    match (start, end) {
+        (Some(start), Some(end)) => &lines[start..=end],
+        _ => &[],
+    }
+}
+
+/// Escape special characters in HTML
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_schema_version() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../schema/output.schema.json")).unwrap();
+        assert_eq!(
+            schema["properties"]["version"]["const"],
+            JSON_OUTPUT_VERSION
+        );
+    }
+
+    #[test]
+    fn test_asciidoc_delimiter() {
+        let lines = vec!["a".to_string(), "-----".to_string(), "".to_string()];
+        assert_eq!(
+            format_asciidoc(&lines, None, &BlockOptions::default()),
+            vec!["[source]", "------", "a", "-----", "------"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Comment pattern presets for different languages
 
-use crate::process::CommentPattern;
+use crate::process::{BlockPattern, CommentPattern, Ellipsis, Nesting};
+use crate::DocMarkup;
 #[cfg(feature = "cli")]
 use clap::ValueEnum;
 use regex::Regex;
@@ -11,19 +12,56 @@ use regex::Regex;
 pub enum Preset {
     /// Rust style
     ///
-    /// Outer comments: `///`
-    /// Inner comments: `//!`
+    /// Outer comments: `///` and `/** ... */`, which can be nested
+    /// Inner comments: `//!` and `/*! ... */`, which can be nested
+    /// Placeholder: `// ...`
+    /// Doc markup: rustdoc
+    /// Attributes: `#[...]`
+    /// Items: `fn`, `struct`, `impl` and the other Rust items
+    /// Sections: `/// # ...` and `// MARK: ...`, and `// region` to `// endregion`
     Rust,
     /// Rust style for single line and Java/JS/TS style for multiline
     ///
     /// Outer comments: `///` and `/** ... */`
     /// Inner comments: `//!` and `/* ... */`
+    /// Trailing comments: `///<`, `//!<` and `/**< ... */`
+    /// Placeholder: `// ...`
+    /// Doc markup: Javadoc/JSDoc
+    /// Attributes: `@...` annotations and decorators
+    /// Sections: `/// # ...` and `// MARK: ...`, and `// #region` or `#region` to the matching end
     RustJava,
     /// Python style
     ///
-    /// Outer comments: empty line
-    /// Inner comments: `###` and `""" ... """`
+    /// Outer comments: `#:`
+    /// Inner comments: `###`, `""" ... """` and `''' ... '''`
+    /// Trailing comments: `#:`
+    /// Placeholder: `...`
+    /// Doc markup: Google/NumPy docstrings
+    /// Attributes: `@...` decorators
+    /// Items: `def`, `class`, assignments and `if __name__`
+    /// Sections: `# MARK: ...`, and `# region` to `# endregion`
     Python,
+    /// C++ style (Doxygen)
+    ///
+    /// Outer comments: `///` and `/** ... */`
+    /// Inner comments: `//!` and `/*! ... */`
+    /// Trailing comments: `///<`, `//!<`, `/**< ... */` and `/*!< ... */`
+    /// Placeholder: `// ...`
+    /// Doc markup: Javadoc/JSDoc
+    /// Attributes: `[[...]]`
+    /// Sections: `/// # ...` and `// MARK: ...`, and `#pragma region` to `#pragma endregion`
+    /// Nesting: braces, so code in namespaces does not need to be indented
+    Cpp,
+    /// Go style
+    ///
+    /// Outer comments: `//` and `/* ... */` directly above a declaration
+    /// Inner comments: none
+    /// Placeholder: `// ...`
+    /// Doc markup: markdown
+    /// Attributes: `//go:...` directives
+    /// Declarations: top level declarations and exported names
+    /// Sections: `// MARK: ...`, and `// region` to `// endregion`
+    Go,
 }
 
 /// Preset implementation
@@ -33,42 +71,290 @@ impl Preset {
     /// Returns (outer, inner)
     pub fn get_patterns(&self) -> (CommentPattern, CommentPattern) {
         match self {
+            // block comments can be nested in rust
             Preset::Rust => (
                 CommentPattern {
-                    single_line: Regex::new(r"^///").unwrap(),
-                    multi_start: None,
-                    multi_end: Regex::new(r"").unwrap(),
+                    single_line: Some(Regex::new(r"^///").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*\*([^*/]|$)").unwrap(),
+                        end: Regex::new(r"\*/").unwrap(),
+                        nested: Some(Regex::new(r"/\*").unwrap()),
+                    }],
+                    transparent: None,
+                    trailing: None,
                 },
                 CommentPattern {
-                    single_line: Regex::new(r"^//!").unwrap(),
-                    multi_start: None,
-                    multi_end: Regex::new(r"").unwrap(),
+                    single_line: Some(Regex::new(r"^//!").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*!").unwrap(),
+                        end: Regex::new(r"\*/").unwrap(),
+                        nested: Some(Regex::new(r"/\*").unwrap()),
+                    }],
+                    transparent: None,
+                    trailing: None,
                 },
             ),
+            // `<` starts trailing comments, like `///<`
             Preset::RustJava => (
                 CommentPattern {
-                    single_line: Regex::new(r"^///|^/\*\*.*\*/\s*$").unwrap(),
-                    multi_start: Some(Regex::new(r"^/\*\*").unwrap()),
-                    multi_end: Regex::new(r"\*/\s*$").unwrap(),
+                    single_line: Some(Regex::new(r"^///([^<]|$)|^/\*\*([^<].*)?\*/\s*$").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*\*([^<]|$)").unwrap(),
+                        end: Regex::new(r"\*/\s*$").unwrap(),
+                        nested: None,
+                    }],
+                    transparent: None,
+                    trailing: Some(
+                        Regex::new(r"^(///<|//!<|/\*\*<)(?P<text>.*?)(\*/\s*)?$").unwrap(),
+                    ),
                 },
                 CommentPattern {
-                    single_line: Regex::new(r"^//!|^/\*[^\*].*\*/\s*$").unwrap(),
-                    multi_start: Some(Regex::new(r"^/\*[^\*]").unwrap()),
-                    multi_end: Regex::new(r"\*/\s*$").unwrap(),
+                    single_line: Some(Regex::new(r"^//!([^<]|$)|^/\*[^\*].*\*/\s*$").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*([^*])").unwrap(),
+                        end: Regex::new(r"\*/\s*$").unwrap(),
+                        nested: None,
+                    }],
+                    transparent: None,
+                    trailing: None,
                 },
             ),
             Preset::Python => (
                 CommentPattern {
-                    single_line: Regex::new(r"^$").unwrap(),
-                    multi_start: None,
-                    multi_end: Regex::new(r"").unwrap(),
+                    single_line: Some(Regex::new(r"^#:").unwrap()),
+                    multi_line: vec![],
+                    transparent: None,
+                    trailing: Some(Regex::new(r"^#:").unwrap()),
+                },
+                CommentPattern {
+                    single_line: Some(Regex::new(r#"^""".*"""\s*$|^'''.*'''\s*$"#).unwrap()),
+                    multi_line: vec![
+                        BlockPattern {
+                            start: Regex::new(r#"^""""#).unwrap(),
+                            end: Regex::new(r#""""\s*$"#).unwrap(),
+                            nested: None,
+                        },
+                        BlockPattern {
+                            start: Regex::new(r"^'''").unwrap(),
+                            end: Regex::new(r"'''\s*$").unwrap(),
+                            nested: None,
+                        },
+                    ],
+                    transparent: None,
+                    trailing: None,
+                },
+            ),
+            // `<` starts trailing comments, like `///<`
+            Preset::Cpp => (
+                CommentPattern {
+                    single_line: Some(Regex::new(r"^///([^<]|$)|^/\*\*([^<].*)?\*/\s*$").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*\*([^<]|$)").unwrap(),
+                        end: Regex::new(r"\*/\s*$").unwrap(),
+                        nested: None,
+                    }],
+                    transparent: None,
+                    trailing: Some(
+                        Regex::new(r"^(///<|//!<|/\*\*<|/\*!<)(?P<text>.*?)(\*/\s*)?$").unwrap(),
+                    ),
                 },
                 CommentPattern {
-                    single_line: Regex::new("^[\"']{3}.*?[\"']{3}\\s*$").unwrap(),
-                    multi_start: Some(Regex::new("^[\"']{3}").unwrap()),
-                    multi_end: Regex::new("[\"']{3}\\s*$").unwrap(),
+                    single_line: Some(Regex::new(r"^//!([^<]|$)|^/\*!([^<].*)?\*/\s*$").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*!([^<]|$)").unwrap(),
+                        end: Regex::new(r"\*/\s*$").unwrap(),
+                        nested: None,
+                    }],
+                    transparent: None,
+                    trailing: None,
                 },
             ),
+            // doc comments are regular comments, so directives like `//go:generate` are excluded.
+            // The package doc is the outer comment of the package clause,
+            // so there are no inner comments
+            Preset::Go => (
+                CommentPattern {
+                    single_line: Some(Regex::new(r"^//(\s|$)|^/\*.*\*/\s*$").unwrap()),
+                    multi_line: vec![BlockPattern {
+                        start: Regex::new(r"^/\*").unwrap(),
+                        end: Regex::new(r"\*/\s*$").unwrap(),
+                        nested: None,
+                    }],
+                    transparent: None,
+                    trailing: None,
+                },
+                CommentPattern {
+                    single_line: None,
+                    multi_line: vec![],
+                    transparent: None,
+                    trailing: None,
+                },
+            ),
+        }
+    }
+
+    /// Get the placeholder for elided lines in the preset
+    pub fn get_ellipsis(&self) -> Ellipsis {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Cpp | Preset::Go => Ellipsis::new("// ..."),
+            Preset::Python => Ellipsis::new("..."),
+        }
+    }
+
+    /// Get the prefix for single line comments in the preset
+    pub fn get_comment_prefix(&self) -> &'static str {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Cpp | Preset::Go => "//",
+            Preset::Python => "#",
+        }
+    }
+
+    /// Get the placeholder for function bodies in the stub format
+    pub fn get_stub_body(&self) -> &'static str {
+        match self {
+            Preset::Rust => "todo!()",
+            Preset::RustJava => "throw new Error();",
+            Preset::Python => "raise NotImplementedError",
+            Preset::Cpp => "throw std::logic_error(\"not implemented\");",
+            Preset::Go => "panic(\"not implemented\")",
+        }
+    }
+
+    /// Get the pattern for the first line of functions in the stub format
+    pub fn get_function_pattern(&self) -> Regex {
+        match self {
+            Preset::Rust => Regex::new(r"\bfn\b").unwrap(),
+            Preset::RustJava => Regex::new(
+                r"\bfunction\b|=>|^\s*(?:[\w$<>\[\],.?]+\s+)*(?:constructor|[A-Za-z_$][\w$]*)\s*(?:<[^>]*>)?\s*\(",
+            )
+            .unwrap(),
+            Preset::Python => Regex::new(r"^\s*(?:async\s+)?def\b").unwrap(),
+            // a name followed by parameters, like `int add(int a, int b)`
+            Preset::Cpp => Regex::new(
+                r"^\s*(?:template\s*<[^>]*>\s*)?(?:[\w:<>,*&~]+\s+)*[\w:~]+\s*\(",
+            )
+            .unwrap(),
+            Preset::Go => Regex::new(r"^\s*func\b").unwrap(),
+        }
+    }
+
+    /// Get the markup style of doc comments in the preset
+    pub fn get_doc_markup(&self) -> DocMarkup {
+        match self {
+            Preset::Rust => DocMarkup::Rustdoc,
+            Preset::RustJava | Preset::Cpp => DocMarkup::Javadoc,
+            Preset::Python => DocMarkup::Python,
+            Preset::Go => DocMarkup::Markdown,
+        }
+    }
+
+    /// Get the name of the grammar for syntax highlighting in the preset
+    ///
+    /// See [`crate::GRAMMARS`]
+    pub fn get_language(&self) -> &'static str {
+        match self {
+            Preset::Rust => "rust",
+            Preset::RustJava | Preset::Cpp | Preset::Go => "clike",
+            Preset::Python => "python",
+        }
+    }
+
+    /// Get the strategy for finding the nesting of components in the preset
+    pub fn get_nesting(&self) -> Nesting {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Python | Preset::Go => Nesting::Indent,
+            Preset::Cpp => Nesting::Brace,
+        }
+    }
+
+    /// Get the pattern for section header comments in the preset
+    pub fn get_section_pattern(&self) -> Regex {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Cpp => {
+                Regex::new(r"^/// #+\s|^//\s*MARK:").unwrap()
+            }
+            Preset::Python => Regex::new(r"^#\s*MARK:").unwrap(),
+            Preset::Go => Regex::new(r"^//\s*MARK:").unwrap(),
+        }
+    }
+
+    /// Get the patterns for the start and end of regions in the preset
+    ///
+    /// Returns (start, end)
+    pub fn get_region_patterns(&self) -> (Regex, Regex) {
+        let (start, end) = match self {
+            Preset::Rust | Preset::Go => (r"^//\s*#?region\b", r"^//\s*#?endregion\b"),
+            // `#region` for C#
+            Preset::RustJava => (
+                r"^//\s*#?region\b|^#region\b",
+                r"^//\s*#?endregion\b|^#endregion\b",
+            ),
+            Preset::Python => (r"^#\s*#?region\b", r"^#\s*#?endregion\b"),
+            Preset::Cpp => (
+                r"^#pragma\s+region\b|^//\s*#?region\b",
+                r"^#pragma\s+endregion\b|^//\s*#?endregion\b",
+            ),
+        };
+        (Regex::new(start).unwrap(), Regex::new(end).unwrap())
+    }
+
+    /// Get the pattern for the last line of multi-line signatures in the preset
+    pub fn get_signature_end(&self) -> Regex {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Cpp => Regex::new(r"[{;]$").unwrap(),
+            Preset::Python => Regex::new(r":$").unwrap(),
+            Preset::Go => Regex::new(r"\{$").unwrap(),
+        }
+    }
+
+    /// Get the pattern for the first line of attributes in the preset
+    pub fn get_attribute_pattern(&self) -> Regex {
+        match self {
+            Preset::Rust => Regex::new(r"^#\[").unwrap(),
+            Preset::RustJava | Preset::Python => Regex::new(r"^@[A-Za-z_]").unwrap(),
+            Preset::Cpp => Regex::new(r"^\[\[").unwrap(),
+            Preset::Go => Regex::new(r"^//[a-z]+:\S").unwrap(),
+        }
+    }
+
+    /// If the outer comments in the preset only count if they end directly above a declaration
+    pub fn is_adjacent(&self) -> bool {
+        matches!(self, Preset::Go)
+    }
+
+    /// Get the pattern for the first line of declarations in the preset, used in adjacency mode
+    pub fn get_declaration_pattern(&self) -> Option<Regex> {
+        match self {
+            Preset::Rust | Preset::RustJava | Preset::Python | Preset::Cpp => None,
+            // top level declarations, and exported fields, methods and constants in groups
+            Preset::Go => Some(
+                Regex::new(
+                    r"^(package|import|func|type|var|const)\b|^[A-Z]\w*(\s*,\s*[A-Z]\w*)*(\s+[^\s:+\-*/%&|^<>!]|\(|$)",
+                )
+                .unwrap(),
+            ),
+        }
+    }
+
+    /// Get the pattern for the first line of items in the preset, which start components
+    /// without outer comments
+    pub fn get_item_pattern(&self) -> Option<Regex> {
+        match self {
+            Preset::Rust => Some(
+                Regex::new(
+                    r#"^(pub(\([^)]*\))?\s+)?((const|async|unsafe|extern(\s+"[^"]*")?)\s+)*(fn|struct|enum|union|trait|impl|mod|type|const|static|macro_rules!)\b"#,
+                )
+                .unwrap(),
+            ),
+            // module and class variables and the main guard too, so they are not in the bodies
+            // of the functions before them
+            Preset::Python => Some(
+                Regex::new(
+                    r"^(async\s+)?def\s|^class\s|^[A-Za-z_]\w*(\s*:[^=]*)?\s*=[^=]|^if\s+__name__\b",
+                )
+                .unwrap(),
+            ),
+            Preset::RustJava | Preset::Cpp | Preset::Go => None,
         }
     }
 }
@@ -1,117 +1,483 @@
 //! Logic for converting a component to a context
 
-use crate::process::{summarize_lines, Component};
+use crate::process::{render_summary_with_kinds, Component, Ellipsis, SummaryLine};
+use crate::{Config, LineKind};
 
 /// Context for a component
 ///
 /// Context is used to prevent expensive cloning of the entire component tree
 /// when the children are not needed
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Context {
     /// Outer comments
     pub outer_comments: Vec<String>,
     /// Beginning body lines
     pub begin_body_lines: Vec<String>,
-    /// Indentation of the component
-    pub indent: usize,
+    /// Characters of the indentation of the component, like four spaces or a tab
+    ///
+    /// See [`Component::indent_prefix`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub indent_prefix: String,
     /// End body lines
     pub end_body_lines: Vec<String>,
+    /// Indices of the placeholders for elided lines in the beginning body lines
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub begin_ellipsis_lines: Vec<usize>,
+    /// Indices of the placeholders for elided lines in the end body lines
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub end_ellipsis_lines: Vec<usize>,
+    /// Range of line numbers of the component in the file
+    ///
+    /// See [`Component::span`]
+    pub span: (usize, usize),
+    /// Name of the component, used for breadcrumbs
+    ///
+    /// This is the first line of the body without the trailing `{` or `:`.
+    /// Empty for the root component.
+    pub name: String,
 }
 
 /// Implementation of Context
 impl Context {
-    /// Create a context from a component
-    pub fn from_component(component: &Component, include_comments: bool) -> Self {
-        let begin_body_lines = get_begin_body_lines(component, include_comments);
-        let end_body_lines = get_end_body_lines(component);
+    /// Create a context from a component and the child that the context is for
+    pub fn from_component(component: &Component, child: &Component, config: &Config) -> Self {
+        let include_comments = config.context_include_comments;
+        let ellipsis = &config.ellipsis;
+        let siblings = if config.context_include_siblings {
+            get_body_lines_with_siblings(component, child, include_comments, ellipsis)
+        } else {
+            None
+        };
+        let (mut begin, end) = siblings.unwrap_or_else(|| {
+            let (child_start, child_end) = find_child_range(component, child);
+            (
+                get_begin_body_lines(component, child_start, include_comments, ellipsis),
+                get_end_body_lines(component, child_end, ellipsis),
+            )
+        });
+
+        if !config.hide_attributes {
+            begin.splice(0..0, component.attributes.iter().cloned().map(context_line));
+        }
+        let (begin_body_lines, begin_ellipsis_lines) = split_kinds(begin);
+        let (end_body_lines, end_ellipsis_lines) = split_kinds(end);
+        let name = get_name(component);
 
         if include_comments {
             Self {
                 outer_comments: component.outer_comments.clone(),
                 begin_body_lines,
-                indent: component.indent,
+                indent_prefix: component.indent_prefix.clone(),
                 end_body_lines,
+                begin_ellipsis_lines,
+                end_ellipsis_lines,
+                span: component.span,
+                name,
             }
         } else {
             Self {
                 outer_comments: vec![],
                 begin_body_lines,
-                indent: component.indent,
+                indent_prefix: component.indent_prefix.clone(),
                 end_body_lines,
+                begin_ellipsis_lines,
+                end_ellipsis_lines,
+                span: component.span,
+                name,
             }
         }
     }
-}
 
-/// Check if the last line in the lines exist and is an indented block
-fn is_last_indented_block(lines: &[String]) -> bool {
-    if let Some(last) = lines.last() {
-        if last.starts_with(super::is_indent_char) {
-            return true;
+    /// Get the beginning body lines with the kind of each line
+    ///
+    /// The placeholders are [`LineKind::Ellipsis`] and the other lines are [`LineKind::Context`]
+    pub fn begin_body_lines_with_kinds(&self) -> Vec<(LineKind, String)> {
+        join_kinds(&self.begin_body_lines, &self.begin_ellipsis_lines)
+    }
+
+    /// Get the end body lines with the kind of each line
+    ///
+    /// See [`Context::begin_body_lines_with_kinds`]
+    pub fn end_body_lines_with_kinds(&self) -> Vec<(LineKind, String)> {
+        join_kinds(&self.end_body_lines, &self.end_ellipsis_lines)
+    }
+
+    /// Remove the placeholders that are printed next to the same placeholder of the parent context
+    ///
+    /// A section has no closing line, so the placeholder for the members after the child is
+    /// directly followed by the placeholder of the parent, like `// ...` twice before `}`.
+    /// Placeholders with different line numbers are kept.
+    pub fn merge_placeholders(&mut self, parent: &Context) {
+        let printed = |line: &str| super::indent_string(line, &parent.indent_prefix);
+        let last = self.end_body_lines.len().checked_sub(1);
+        if last.is_some()
+            && self.end_ellipsis_lines.last().copied() == last
+            && parent.end_ellipsis_lines.first() == Some(&0)
+            && self.end_body_lines.last().map(|line| printed(line))
+                == parent.end_body_lines.first().cloned()
+        {
+            self.end_body_lines.pop();
+            self.end_ellipsis_lines.pop();
         }
+        let parent_last = parent.begin_body_lines.len().checked_sub(1);
+        if parent_last.is_some()
+            && self.outer_comments.is_empty()
+            && self.begin_ellipsis_lines.first() == Some(&0)
+            && parent.begin_ellipsis_lines.last().copied() == parent_last
+            && self.begin_body_lines.first().map(|line| printed(line))
+                == parent.begin_body_lines.last().cloned()
+        {
+            self.begin_body_lines.remove(0);
+            self.begin_ellipsis_lines.remove(0);
+            self.begin_ellipsis_lines.iter_mut().for_each(|i| *i -= 1);
+        }
+    }
+
+    /// Get the number of lines printed for the context
+    pub fn line_count(&self) -> usize {
+        self.outer_comments.len() + self.begin_body_lines.len() + self.end_body_lines.len()
     }
-    false
 }
-fn get_begin_body_lines(component: &Component, include_comments: bool) -> Vec<String> {
+
+/// Lines of the context with the kind of each line
+type ContextLines = Vec<(LineKind, String)>;
+
+/// Mark a line as a line of the context
+fn context_line(line: String) -> (LineKind, String) {
+    (LineKind::Context, line)
+}
+
+/// Split the lines into the lines and the indices of the placeholders
+fn split_kinds(lines: Vec<(LineKind, String)>) -> (Vec<String>, Vec<usize>) {
+    let ellipsis_lines = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (kind, _))| *kind == LineKind::Ellipsis)
+        .map(|(i, _)| i)
+        .collect();
+    let lines = lines.into_iter().map(|(_, line)| line).collect();
+    (lines, ellipsis_lines)
+}
+
+/// Add the kinds back to the lines split by [`split_kinds`]
+fn join_kinds(lines: &[String], ellipsis_lines: &[usize]) -> Vec<(LineKind, String)> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if ellipsis_lines.contains(&i) {
+                (LineKind::Ellipsis, line.clone())
+            } else {
+                context_line(line.clone())
+            }
+        })
+        .collect()
+}
+
+/// Render the summary view of the body lines as lines of the context
+fn render_context_summary(
+    summary: &[SummaryLine],
+    lines: &[String],
+    line_numbers: &[usize],
+    indent: &str,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    render_summary_with_kinds(summary, lines, line_numbers, indent, ellipsis)
+        .into_iter()
+        .map(|(kind, line)| match kind {
+            LineKind::Target => context_line(line),
+            kind => (kind, line),
+        })
+        .collect()
+}
+
+/// Get the name of the component for breadcrumbs
+fn get_name(component: &Component) -> String {
     if component.is_root {
-        let mut l: Vec<String> = if include_comments {
-            component
-                .inner_comments
+        return String::new();
+    }
+    match component.body_lines.first() {
+        Some(line) => line
+            .trim()
+            .trim_end_matches(['{', ':'])
+            .trim_end()
+            .to_string(),
+        None => String::new(),
+    }
+}
+
+/// Find the range of the child in the body lines of the component
+///
+/// Returns the start (inclusive) and end (exclusive) indices
+fn find_child_range(component: &Component, child: &Component) -> (usize, usize) {
+    let (start_line, end_line) = child.span;
+    let line_numbers = &component.body_line_numbers;
+    let start = line_numbers
+        .iter()
+        .position(|n| *n >= start_line)
+        .unwrap_or(line_numbers.len());
+    let end = line_numbers
+        .iter()
+        .position(|n| *n >= end_line)
+        .unwrap_or(line_numbers.len());
+    (start, end)
+}
+
+/// Find the ... block in the summary that has the line at the index
+///
+/// Returns the last ... block if no block has the line, or None if there are no ... blocks
+fn find_elided_block(summary: &[SummaryLine], index: usize) -> Option<usize> {
+    summary
+        .iter()
+        .position(|line| matches!(line, SummaryLine::Elided(start, end) if (*start..*end).contains(&index)))
+        .or_else(|| {
+            summary
                 .iter()
-                .map(|s| super::indent_string(s, component.indent))
-                .collect()
+                .rposition(|line| matches!(line, SummaryLine::Elided(_, _)))
+        })
+}
+
+/// Render the placeholder for the body lines in the range
+///
+/// Returns None if the range is empty
+fn render_ellipsis(
+    component: &Component,
+    start: usize,
+    end: usize,
+    ellipsis: &Ellipsis,
+) -> Option<(LineKind, String)> {
+    let end = end.max(start);
+    ellipsis
+        .render(
+            &component.body_line_numbers[start..end],
+            &component.indent_prefix,
+        )
+        .map(|line| (LineKind::Ellipsis, line))
+}
+
+/// Get the begin and end body lines, with the siblings of the child in between
+///
+/// Each sibling is collapsed to its signature, with the outer comments
+/// if `include_comments` is true. See [`collapse_sibling`].
+///
+/// Returns None if the child is not one of the children of the component
+fn get_body_lines_with_siblings(
+    component: &Component,
+    child: &Component,
+    include_comments: bool,
+    ellipsis: &Ellipsis,
+) -> Option<(ContextLines, ContextLines)> {
+    let children = &component.children;
+    let index = children.iter().position(|c| c.span == child.span)?;
+    let (first_start, _) = find_child_range(component, &children[0]);
+    let (_, last_end) = find_child_range(component, &children[children.len() - 1]);
+
+    let mut begin = get_begin_body_lines(component, first_start, include_comments, ellipsis);
+    begin.append(&mut get_sibling_lines(
+        &children[..index],
+        include_comments,
+        &component.indent_prefix,
+        ellipsis,
+    ));
+    let mut end = get_sibling_lines(
+        &children[index + 1..],
+        include_comments,
+        &component.indent_prefix,
+        ellipsis,
+    );
+    end.append(&mut get_end_body_lines(component, last_end, ellipsis));
+
+    Some((begin, end))
+}
+
+/// Collapse the siblings, with the outer comments if `include_comments` is true
+fn get_sibling_lines(
+    siblings: &[Component],
+    include_comments: bool,
+    indent: &str,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    let mut l = vec![];
+    siblings.iter().for_each(|sibling| {
+        if include_comments {
+            sibling
+                .outer_comments
+                .iter()
+                .for_each(|s| l.push(context_line(super::indent_string(s, indent))));
+        }
+        collapse_sibling(sibling, ellipsis)
+            .into_iter()
+            .for_each(|(kind, s)| l.push((kind, super::indent_string(&s, indent))));
+    });
+    l
+}
+
+/// Collapse a sibling to its signature, a placeholder for the rest of the body and the closing line
+///
+/// With brace nesting, the placeholder replaces the lines in the block and the lines after it are kept.
+/// Otherwise, the closing line is the last non-empty body line if it is not indented and not
+/// inside a string literal, like `}`.
+fn collapse_sibling(sibling: &Component, ellipsis: &Ellipsis) -> Vec<(LineKind, String)> {
+    let lines = &sibling.body_lines;
+    let (elided_start, elided_end) = match sibling.block {
+        Some(block) => block,
+        None => {
+            let signature_end = sibling.signature.map_or(1, |(_, end)| end);
+            // the empty lines at the end are kept
+            let elided_end = match lines.iter().rposition(|line| !line.trim().is_empty()) {
+                Some(i)
+                    if i >= signature_end
+                        && !lines[i].starts_with(super::is_indent_char)
+                        && sibling.literal_lines.binary_search(&i).is_err() =>
+                {
+                    i
+                }
+                Some(i) => i + 1,
+                None => lines.len(),
+            };
+            (signature_end, elided_end)
+        }
+    };
+    let elided_start = elided_start.min(lines.len());
+    let elided_end = elided_end.clamp(elided_start, lines.len());
+    let mut l = lines[..elided_start]
+        .iter()
+        .cloned()
+        .map(context_line)
+        .collect::<Vec<_>>();
+    l.extend(
+        ellipsis
+            .render(
+                &sibling.body_line_numbers[elided_start..elided_end],
+                &sibling.indent_prefix,
+            )
+            .map(|line| (LineKind::Ellipsis, line)),
+    );
+    l.extend(lines[elided_end..].iter().cloned().map(context_line));
+    l
+}
+
+fn get_begin_body_lines(
+    component: &Component,
+    child_start: usize,
+    include_comments: bool,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    let indent = component.indent_prefix.as_str();
+    if component.is_root {
+        let (mut l, elided_start): (Vec<(LineKind, String)>, usize) = if include_comments {
+            (
+                component
+                    .inner_comments
+                    .iter()
+                    .map(|s| context_line(super::indent_string(s, indent)))
+                    .collect(),
+                component.inner_comments_range.map_or(0, |(_, end)| end),
+            )
         } else {
-            vec![]
+            (vec![], 0)
         };
-        l.push(super::indent_string("...", component.indent));
+        l.extend(render_ellipsis(
+            component,
+            elided_start,
+            child_start,
+            ellipsis,
+        ));
         return l;
     }
+    let body_lines = &component.body_lines;
+    let line_numbers = &component.body_line_numbers;
     match component.inner_comments_range {
-        Some((start, _)) => {
-            let mut l = summarize_lines(&component.body_lines[..start], component.indent, None);
+        Some((start, end)) => {
+            let mut summary = component.summarize_body(0, start, None);
             // add the inner comments if need
-            if include_comments {
-                component
-                    .inner_comments
-                    .iter()
-                    .for_each(|s| l.push(super::indent_string(s, component.indent)));
-                l.push(super::indent_string("...", component.indent));
-            } else if !is_last_indented_block(&l) {
-                // add the ... block if needed
-                l.push(super::indent_string("...", component.indent));
-            }
+            let mut inner_comments = vec![];
+            let elided_start = if include_comments {
+                component.inner_comments.iter().for_each(|s| {
+                    inner_comments.push(context_line(super::indent_string(s, indent)))
+                });
+                end
+            } else {
+                // extend the last ... block if needed
+                match summary.last() {
+                    Some(SummaryLine::Elided(elided_start, _)) => {
+                        let elided_start = *elided_start;
+                        summary.pop();
+                        elided_start
+                    }
+                    _ => start,
+                }
+            };
+            let mut l =
+                render_context_summary(&summary, body_lines, line_numbers, indent, ellipsis);
+            l.append(&mut inner_comments);
+            l.extend(render_ellipsis(
+                component,
+                elided_start,
+                child_start,
+                ellipsis,
+            ));
             l
         }
         None => {
-            let mut l = summarize_lines(&component.body_lines, component.indent, None);
-            // Remove until last ... block
-            while !is_last_indented_block(&l) {
-                if l.pop().is_none() {
-                    break;
+            let mut summary = component.summarize_body(0, body_lines.len(), None);
+            // Remove until the ... block that has the child
+            let block = find_elided_block(&summary, child_start);
+            summary.truncate(block.map_or(0, |i| i + 1));
+            // the ... block ends at the child
+            if let Some(SummaryLine::Elided(start, end)) = summary.last_mut() {
+                *end = child_start;
+                if *start >= *end {
+                    summary.pop();
                 }
             }
-            l
+            render_context_summary(&summary, body_lines, line_numbers, indent, ellipsis)
         }
     }
 }
 
-fn get_end_body_lines(component: &Component) -> Vec<String> {
+fn get_end_body_lines(
+    component: &Component,
+    child_end: usize,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    let indent = component.indent_prefix.as_str();
     if component.is_root {
-        return vec![super::indent_string("...", component.indent)];
+        return render_ellipsis(component, child_end, component.body_lines.len(), ellipsis)
+            .into_iter()
+            .collect();
     }
-    let mut l = match component.inner_comments_range {
-        Some((_, end)) => summarize_lines(&component.body_lines[end..], component.indent, None),
-        None => summarize_lines(&component.body_lines, component.indent, None),
+    let offset = match component.inner_comments_range {
+        Some((_, end)) => end,
+        None => 0,
     };
-    // only keep the last ... block
-    let mut last = vec![];
-    while !is_last_indented_block(&l) {
-        match l.pop() {
-            Some(s) => last.push(s),
-            None => break,
-        }
-    }
-    last.push(super::indent_string("...", component.indent));
-    last.reverse();
-    last
+    let body_lines = &component.body_lines[offset..];
+    let line_numbers = &component.body_line_numbers[offset..];
+    let mut summary = component.summarize_body(offset, component.body_lines.len(), None);
+    // only keep the lines after the ... block that has the child
+    let block = find_elided_block(&summary, child_end.saturating_sub(offset + 1));
+    let last = summary.split_off(block.map_or(summary.len(), |i| i + 1));
+    let elided_end = match last.first() {
+        Some(SummaryLine::Line(i)) => offset + i,
+        _ => component.body_lines.len(),
+    };
+    let mut l: Vec<(LineKind, String)> =
+        render_ellipsis(component, child_end, elided_end, ellipsis)
+            .into_iter()
+            .collect();
+    l.append(&mut render_context_summary(
+        &last,
+        body_lines,
+        line_numbers,
+        indent,
+        ellipsis,
+    ));
+    l
 }
@@ -0,0 +1,79 @@
+//! Logic and tests for rendering the placeholder of elided lines
+
+/// Template for the placeholder that replaces elided lines
+///
+/// The template can contain the following variables:
+/// - `{lines}`: the number of elided lines
+/// - `{start}`: the line number of the first elided line
+/// - `{end}`: the line number of the last elided line
+///
+/// Line numbers start from 1. No placeholder is rendered if no lines are elided.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ellipsis {
+    /// The template string
+    pub template: String,
+}
+
+/// Default placeholder is `...`
+impl Default for Ellipsis {
+    fn default() -> Self {
+        Self::new("...")
+    }
+}
+
+/// Implementation of Ellipsis
+impl Ellipsis {
+    /// Create an ellipsis from a template string
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+        }
+    }
+
+    /// Render the placeholder for the elided lines
+    ///
+    /// The line numbers are the line numbers of the elided lines in the file.
+    /// The output is indented by `indent`, like a tab. Returns None if no lines are elided
+    pub fn render(&self, line_numbers: &[usize], indent: &str) -> Option<String> {
+        let (start, end) = match (line_numbers.first(), line_numbers.last()) {
+            (Some(start), Some(end)) => (start.to_string(), end.to_string()),
+            _ => return None,
+        };
+        let line = self
+            .template
+            .replace("{lines}", &line_numbers.len().to_string())
+            .replace("{start}", &start)
+            .replace("{end}", &end);
+
+        Some(super::indent_string(&line, indent))
+    }
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        assert_eq!(
+            Ellipsis::default().render(&[1, 2, 3], "    ").unwrap(),
+            "    ..."
+        );
+    }
+
+    #[test]
+    fn test_variables() {
+        let ellipsis = Ellipsis::new("/* {lines} lines: {start}-{end} */");
+        assert_eq!(
+            ellipsis.render(&[3, 4, 6], "\t").unwrap(),
+            "\t/* 3 lines: 3-6 */"
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let ellipsis = Ellipsis::new("// {lines} lines {start}{end}");
+        assert_eq!(ellipsis.render(&[], ""), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! Logic and tests for extracting comment text and code examples from comments
+
+use crate::process::{is_indent_char, CommentPattern, Component, OpenBlock};
+use crate::Config;
+
+/// A code example found in the comments
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Example {
+    /// Language of the example, from the info string of the fence
+    pub language: Option<String>,
+    /// Lines of the example code, unindented
+    pub lines: Vec<String>,
+}
+
+/// Get the text of comment lines with the comment markers removed
+///
+/// The text is unindented by the common indentation of the non-empty lines.
+/// The transparent lines between the blocks are removed.
+pub fn comment_text(lines: &[String], pattern: &CommentPattern) -> Vec<String> {
+    let mut text = vec![];
+    // the open comment, and if the lines may start with `*`
+    let mut in_block: Option<(OpenBlock, bool)> = None;
+    for raw in lines {
+        let trimmed = raw.trim_start();
+        let mut line = raw.as_str();
+        match &mut in_block {
+            None => match pattern.open_block(trimmed) {
+                Some((mut block, start)) => {
+                    let star_continuation = start[0].contains('*');
+                    line = &trimmed[marker_end(&start)..];
+                    match block.find_end(line) {
+                        Some((end, _)) => line = &line[..end],
+                        None => in_block = Some((block, star_continuation)),
+                    }
+                }
+                None => {
+                    if let Some(captures) = pattern.find_single_line(trimmed) {
+                        if captures.get(0).is_some_and(|m| m.start() == 0) {
+                            line = &trimmed[marker_end(&captures)..];
+                        }
+                    } else if let Some(text) = pattern.trailing_text(trimmed) {
+                        line = text;
+                    } else if pattern
+                        .transparent
+                        .as_ref()
+                        .is_some_and(|pattern| pattern.is_match(trimmed))
+                    {
+                        // like in find_comments, the markers are checked first
+                        continue;
+                    }
+                }
+            },
+            Some((block, star_continuation)) => {
+                // the indentation is kept unless the lines start with `*`
+                if *star_continuation {
+                    line = trimmed;
+                }
+                if let Some((end, _)) = block.find_end(line) {
+                    line = &line[..end];
+                    if *star_continuation {
+                        line = line.strip_prefix('*').unwrap_or(line);
+                    }
+                    in_block = None;
+                } else if *star_continuation {
+                    line = line.strip_prefix('*').unwrap_or(line);
+                }
+            }
+        }
+        text.push(line.trim_end().to_string());
+    }
+
+    unindent_text(text)
+}
+
+/// Get the end of the comment marker in a match of a comment pattern
+///
+/// Patterns like `^///([^<]|$)` match the character after the marker in a group at the
+/// end of the match, so the marker ends where that group starts. Otherwise, a space
+/// matched after the marker is not removed so that the lines are unindented the same
+/// way as the other lines.
+fn marker_end(captures: &regex::Captures) -> usize {
+    let m = match captures.get(0) {
+        Some(m) => m,
+        None => return 0,
+    };
+    let group = captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .filter(|group| group.start() > m.start() && group.end() == m.end())
+        .last();
+    if let Some(group) = group {
+        group.start()
+    } else if m.as_str().len() > 1 && m.as_str().ends_with(is_indent_char) {
+        m.end() - 1
+    } else {
+        m.end()
+    }
+}
+
+/// Get the text of the outer and inner comments of a component, separated by an empty line
+///
+/// The trailing comments are outer comments.
+pub fn component_comment_text(component: &Component, config: &Config) -> Vec<String> {
+    let mut text = outer_comment_text(component, config);
+    let inner = comment_text(&component.inner_comments, &config.inner_comments);
+    if !text.is_empty() && !inner.is_empty() {
+        text.push(String::new());
+    }
+    text.extend(inner);
+    text
+}
+
+/// Get the text of the outer comments and the trailing comments of a component
+fn outer_comment_text(component: &Component, config: &Config) -> Vec<String> {
+    let lines = [
+        component.outer_comments.as_slice(),
+        component.trailing_comments.as_slice(),
+    ]
+    .concat();
+    comment_text(&lines, &config.outer_comments)
+}
+
+/// Remove the common indentation of the non-empty lines
+fn unindent_text(lines: Vec<String>) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start_matches(is_indent_char).len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .into_iter()
+        .map(|line| line.get(indent..).unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Find the code examples in comment text
+///
+/// Examples are either fenced with ```` ``` ```` or `~~~`, or doctest blocks
+/// that start with `>>>`. For doctests, the prompts are removed and the expected output is skipped.
+///
+/// For Rust examples, hidden lines starting with `# ` are unhidden.
+/// `default_language` is used for fences without an info string.
+pub fn find_examples(text: &[String], default_language: Option<&str>) -> Vec<Example> {
+    let mut examples = vec![];
+    let mut i = 0;
+    while i < text.len() {
+        let line = text[i].trim_start();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            let fence = &line[..3];
+            let info = line[3..].trim();
+            let language = match info.split(',').next().unwrap_or_default() {
+                "" => default_language.map(|s| s.to_string()),
+                language => Some(language.to_string()),
+            };
+            let start = i + 1;
+            i = start;
+            while i < text.len() && !text[i].trim_start().starts_with(fence) {
+                i += 1;
+            }
+            let mut lines = unindent_text(text[start..i].to_vec());
+            if language.as_deref() == Some("rust") {
+                lines = lines.into_iter().map(unhide_rust_line).collect();
+            }
+            examples.push(Example { language, lines });
+        } else if line.starts_with(">>>") {
+            let mut lines = vec![];
+            while i < text.len() && !text[i].trim().is_empty() {
+                let line = text[i].trim_start();
+                if let Some(code) = line
+                    .strip_prefix(">>>")
+                    .or_else(|| line.strip_prefix("..."))
+                {
+                    lines.push(code.strip_prefix(' ').unwrap_or(code).to_string());
+                }
+                i += 1;
+            }
+            examples.push(Example {
+                language: Some("python".to_string()),
+                lines,
+            });
+        }
+        i += 1;
+    }
+
+    examples
+}
+
+/// Unhide a hidden line in a Rust example
+fn unhide_rust_line(line: String) -> String {
+    let indent = line.len() - line.trim_start_matches(is_indent_char).len();
+    match line[indent..].strip_prefix('#') {
+        Some("") => String::new(),
+        Some(rest) if rest.starts_with(' ') => format!("{}{}", &line[..indent], &rest[1..]),
+        _ => line,
+    }
+}
+
+/// Find the code examples in the outer and inner comments of a component
+pub fn find_component_examples(component: &Component, config: &Config) -> Vec<Example> {
+    let language = config.language.as_deref();
+    let mut examples = find_examples(&outer_comment_text(component, config), language);
+    examples.extend(find_examples(
+        &comment_text(&component.inner_comments, &config.inner_comments),
+        language,
+    ));
+    examples
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+    use crate::process::BlockPattern;
+    use regex::Regex;
+
+    fn rust_java() -> CommentPattern {
+        CommentPattern {
+            single_line: Some(Regex::new(r"^///|^/\*\*.*\*/\s*$").unwrap()),
+            multi_line: vec![BlockPattern {
+                start: Regex::new(r"^/\*\*").unwrap(),
+                end: Regex::new(r"\*/\s*$").unwrap(),
+                nested: None,
+            }],
+            transparent: None,
+            trailing: None,
+        }
+    }
+
+    fn to_lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_comment_text_single_line() {
+        let lines = to_lines(&["/// Hello", "///", "///     indented", "/** one line */"]);
+        assert_eq!(
+            comment_text(&lines, &rust_java()),
+            to_lines(&["Hello", "", "    indented", "one line"])
+        );
+    }
+
+    #[test]
+    fn test_comment_text_trailing() {
+        let pattern = CommentPattern {
+            single_line: Some(Regex::new(r"^///([^<]|$)").unwrap()),
+            trailing: Some(Regex::new(r"^///<").unwrap()),
+            ..rust_java()
+        };
+        let lines = to_lines(&["///< the red channel", "/// Transparency", "///"]);
+        assert_eq!(
+            comment_text(&lines, &pattern),
+            to_lines(&["the red channel", "Transparency", ""])
+        );
+        // the character after the marker is kept
+        let lines = to_lines(&["///Add", "///  indented"]);
+        assert_eq!(
+            comment_text(&lines, &pattern),
+            to_lines(&["Add", "  indented"])
+        );
+    }
+
+    #[test]
+    fn test_comment_text_multi_line() {
+        let lines = to_lines(&["/**", " * Hello", " *", " *   world", " */"]);
+        assert_eq!(
+            comment_text(&lines, &rust_java()),
+            to_lines(&["", "Hello", "", "  world", ""])
+        );
+    }
+
+    #[test]
+    fn test_comment_text_docstring() {
+        let pattern = CommentPattern {
+            single_line: Some(Regex::new("^[\"']{3}.*?[\"']{3}\\s*$").unwrap()),
+            multi_line: vec![BlockPattern {
+                start: Regex::new("^[\"']{3}").unwrap(),
+                end: Regex::new("[\"']{3}\\s*$").unwrap(),
+                nested: None,
+            }],
+            transparent: None,
+            trailing: None,
+        };
+        let lines = to_lines(&["\"\"\"Doc", "    * item", "        >>> 1", "    \"\"\""]);
+        assert_eq!(
+            comment_text(&lines, &pattern),
+            to_lines(&["Doc", "    * item", "        >>> 1", ""])
+        );
+    }
+
+    #[test]
+    fn test_fenced() {
+        let text = to_lines(&[
+            "Example:",
+            "```",
+            "# fn main() {",
+            "let x = 1;",
+            "    #[cfg(test)]",
+            "#",
+            "# }",
+            "```",
+            "  ~~~ts,ignore",
+            "  let y = 2;",
+            "  ~~~",
+        ]);
+        assert_eq!(
+            find_examples(&text, Some("rust")),
+            vec![
+                Example {
+                    language: Some("rust".to_string()),
+                    lines: to_lines(&["fn main() {", "let x = 1;", "    #[cfg(test)]", "", "}"]),
+                },
+                Example {
+                    language: Some("ts".to_string()),
+                    lines: to_lines(&["let y = 2;"]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_doctest() {
+        let text = to_lines(&[
+            ">>> x = [1,",
+            "...      2]",
+            ">>> x",
+            "[1, 2]",
+            "",
+            ">>> print(x)",
+        ]);
+        assert_eq!(
+            find_examples(&text, None),
+            vec![
+                Example {
+                    language: Some("python".to_string()),
+                    lines: to_lines(&["x = [1,", "     2]", "x"]),
+                },
+                Example {
+                    language: Some("python".to_string()),
+                    lines: to_lines(&["print(x)"]),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Logic and tests for finding the extent of components by balancing braces
+
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use regex::Regex;
+
+/// Strategy for finding the nesting of components
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Nesting {
+    /// Children are in the indented blocks, and a component ends at the next line that is not indented
+    #[default]
+    Indent,
+    /// Children are inside the `{` and `}` of the component, regardless of the indentation,
+    /// and a component ends at the matching `}` or at the `;` if it has no braces.
+    ///
+    /// Braces in C-like strings and comments are ignored.
+    Brace,
+}
+
+/// Scan the braces in the lines
+///
+/// Returns the brace depth at the end of each line, and if the line has an opening brace.
+/// See [`scan_brackets`] for what is ignored.
+pub fn scan_braces(lines: &[String], lifetimes: bool) -> Vec<(usize, bool)> {
+    scan_brackets(lines, &['{'], &['}'], lifetimes)
+}
+
+/// Scan the brackets in the lines
+///
+/// Returns the bracket depth at the end of each line, and if the line has an opening bracket.
+/// Brackets in strings, character literals and `//` or `/* */` comments are ignored.
+/// Unmatched closing brackets are ignored. If `lifetimes` is true, a `'` that is not closed
+/// right after the next character is a Rust lifetime or label like `'a`, not a character literal.
+/// Otherwise, `'` always starts a string, like in JavaScript.
+pub fn scan_brackets(
+    lines: &[String],
+    open: &[char],
+    close: &[char],
+    lifetimes: bool,
+) -> Vec<(usize, bool)> {
+    let mut depth: usize = 0;
+    let mut in_block_comment = false;
+    // template literals in JS can span multiple lines
+    let mut in_template = false;
+    let mut output = vec![];
+    for line in lines {
+        let mut opens = false;
+        let mut chars = line.chars().peekable();
+        let mut quote: Option<char> = in_template.then_some('`');
+        while let Some(c) = chars.next() {
+            if in_block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            if let Some(q) = quote {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    in_block_comment = true;
+                }
+                '\'' if lifetimes && is_lifetime(chars.clone()) => {}
+                '"' | '\'' | '`' => quote = Some(c),
+                c if open.contains(&c) => {
+                    depth += 1;
+                    opens = true;
+                }
+                c if close.contains(&c) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        in_template = quote == Some('`');
+        output.push((depth, opens));
+    }
+
+    output
+}
+
+/// Check if the characters after a `'` are a lifetime or label like `'a`
+fn is_lifetime(mut chars: impl Iterator<Item = char>) -> bool {
+    match (chars.next(), chars.next()) {
+        (Some(c), next) => (c.is_alphabetic() || c == '_') && next != Some('\''),
+        _ => false,
+    }
+}
+
+/// Find the lines inside the first pair of braces
+///
+/// Returns the start (inclusive) and end (exclusive) indices of the lines between the line
+/// with the opening brace and the line with the closing brace.
+/// Returns None if there are no braces, or if there are no lines between them.
+/// See [`scan_brackets`] for `lifetimes`.
+pub fn find_brace_block(lines: &[String], lifetimes: bool) -> Option<(usize, usize)> {
+    let braces = scan_braces(lines, lifetimes);
+    let open = braces.iter().position(|(depth, _)| *depth > 0)?;
+    let close = braces[open..]
+        .iter()
+        .position(|(depth, _)| *depth == 0)
+        .map_or(lines.len(), |i| open + i);
+    if open + 1 >= close {
+        return None;
+    }
+
+    Some((open + 1, close))
+}
+
+/// Find the end of the signature of a component
+///
+/// The signature starts at the first non-empty line, and ends after the first line where the
+/// parentheses and square brackets are balanced and that matches the pattern, like `{` or `:`
+/// at the end of the line. If no line matches, the signature is the first line.
+/// Without a pattern, the signature ends after the first line where the brackets are balanced.
+///
+/// Returns the start (inclusive) and end (exclusive) indices, or None if all lines are empty.
+/// See [`scan_brackets`] for `lifetimes`.
+pub fn find_signature(
+    lines: &[String],
+    end_pattern: Option<&Regex>,
+    lifetimes: bool,
+) -> Option<(usize, usize)> {
+    let start = lines.iter().position(|line| !line.trim().is_empty())?;
+    let brackets = scan_brackets(&lines[start..], &['(', '['], &[')', ']'], lifetimes);
+    let balanced = |i: &usize| brackets[i - start].0 == 0;
+    let end = match end_pattern {
+        Some(pattern) => (start..lines.len())
+            .filter(balanced)
+            .find(|i| pattern.is_match(lines[*i].trim_end()))
+            .unwrap_or(start),
+        None => (start..lines.len())
+            .find(balanced)
+            .unwrap_or(lines.len() - 1),
+    };
+
+    Some((start, end + 1))
+}
+
+/// Find the end of the statement that starts at `start`
+///
+/// The statement ends after the line where its braces are balanced, or after the line
+/// that ends with `;` if it has no braces. `braces` is the output of [`scan_braces`],
+/// where the depth at the start of the statement is 0.
+///
+/// Returns the end index (exclusive)
+pub fn find_statement_end(lines: &[String], braces: &[(usize, bool)], start: usize) -> usize {
+    let mut opened = false;
+    for i in start..lines.len() {
+        let (depth, opens) = braces[i];
+        opened |= opens;
+        if depth == 0 && (opened || lines[i].trim_end().ends_with(';')) {
+            return i + 1;
+        }
+    }
+
+    lines.len()
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    fn to_lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_scan_ignores_strings_and_comments() {
+        let lines = to_lines(&[
+            "namespace a {",
+            "  auto s = \"}\"; char c = '{';",
+            "  // }",
+            "  /* } ",
+            "  { */ int x = `{",
+            "  }`;",
+            "} // end namespace",
+        ]);
+        assert_eq!(
+            scan_braces(&lines, false),
+            vec![
+                (1, true),
+                (1, false),
+                (1, false),
+                (1, false),
+                (1, false),
+                (1, false),
+                (0, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_lifetimes() {
+        let lines = to_lines(&["fn f<'a>(x: &'a str, c: char = '(') {", "'outer: loop {"]);
+        assert_eq!(
+            scan_brackets(&lines, &['(', '{'], &[')', '}'], true),
+            vec![(1, true), (2, true)]
+        );
+        // without lifetimes, `'` always starts a string
+        let lines = to_lines(&["const s = 'ab{';", "class A {"]);
+        assert_eq!(scan_braces(&lines, false), vec![(0, false), (1, true)]);
+    }
+
+    #[test]
+    fn test_signature() {
+        let lines = to_lines(&[
+            "",
+            "pub fn foo<T>(",
+            "    a: T,",
+            ") -> T",
+            "where",
+            "    T: Clone,",
+            "{",
+            "    a",
+            "}",
+        ]);
+        let pattern = Regex::new(r"[{;]$").unwrap();
+        assert_eq!(find_signature(&lines, Some(&pattern), false), Some((1, 7)));
+        assert_eq!(find_signature(&lines, None, false), Some((1, 4)));
+        let lines = to_lines(&["A(", "    u32,", "),"]);
+        assert_eq!(find_signature(&lines, Some(&pattern), false), Some((0, 1)));
+        assert_eq!(find_signature(&to_lines(&[""]), None, false), None);
+    }
+
+    #[test]
+    fn test_brace_block() {
+        let lines = to_lines(&["void f()", "{", "    int x;", "}", "int y;"]);
+        assert_eq!(find_brace_block(&lines, false), Some((2, 3)));
+        let lines = to_lines(&["struct A {};", "int y;"]);
+        assert_eq!(find_brace_block(&lines, false), None);
+        let lines = to_lines(&["namespace a {", "int x;"]);
+        assert_eq!(find_brace_block(&lines, false), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_statement_end() {
+        let lines = to_lines(&["int x", "    = 1;", "void f() {}", "class A {", "};", "x"]);
+        let braces = scan_braces(&lines, false);
+        assert_eq!(find_statement_end(&lines, &braces, 0), 2);
+        assert_eq!(find_statement_end(&lines, &braces, 2), 3);
+        assert_eq!(find_statement_end(&lines, &braces, 3), 5);
+        assert_eq!(find_statement_end(&lines, &braces, 5), 6);
+    }
+}
@@ -1,32 +1,199 @@
 //! Logic and tests for finding comments from list of lines
 
-use regex::Regex;
+use std::ops::Range;
+
+use regex::{Captures, Regex};
 
 /// Regex patterns for comments
 ///
 /// The patterns should match the entire line.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommentPattern {
     /// Pattern for single-line comments
-    pub single_line: Regex,
-    /// Pattern for the first line of multi line comments
     ///
-    /// If none, then multi line comments are not supported
-    pub multi_start: Option<Regex>,
-    /// Pattern for the last line of the multi line comments
+    /// If None, then single-line comments are not supported
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none", with = "serde_regex")
+    )]
+    pub single_line: Option<Regex>,
+    /// Patterns for multi line comments
+    ///
+    /// A comment that is started by one of the patterns is only ended by the same pattern.
+    /// If empty, then multi line comments are not supported
+    pub multi_line: Vec<BlockPattern>,
+    /// Pattern for lines that are allowed between the blocks of a comment
+    ///
+    /// These lines, like `// SAFETY:` or lint suppressions, are part of the comment if they are
+    /// followed by another block, but are not part of the comment text.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none", with = "serde_regex")
+    )]
+    pub transparent: Option<Regex>,
+    /// Pattern for trailing comments after the code of a component, like `///<` or `#:`
     ///
-    /// The last line must not be the start line.
+    /// The pattern is matched at the start of the line or after a space or tab, like
+    /// `Red, ///< the red channel`. A trailing comment on its own line is for the line above.
+    /// If the pattern has a group named `text`, it is the text of the comment.
+    /// Otherwise, the text is after the match.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none", with = "serde_regex")
+    )]
+    pub trailing: Option<Regex>,
+}
+
+/// Regex patterns for the start and end of multi line comments
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockPattern {
+    /// Pattern for the first line of the comments
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub start: Regex,
+    /// Pattern for the end of the comments
+    ///
+    /// The last line must not be the start line, unless the comments are nested.
     /// If the comment syntax supports single-line block comment, then
     /// the single line regex should support that.
     ///
-    /// Ignored if multi_start is None.
-    pub multi_end: Regex,
+    /// Named groups that are also in the start pattern must match the same text as in
+    /// the start, like `(?P<level>=*)` for the level of Lua long brackets.
+    #[cfg_attr(feature = "serde", serde(with = "serde_regex"))]
+    pub end: Regex,
+    /// Pattern for the start of nested comments, if the comments can be nested
+    ///
+    /// Each match increases the depth and each match of the end pattern decreases it,
+    /// so the comments end where the depth is back to 0. The end pattern should not be
+    /// anchored to the end of the line for nested comments.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none", with = "serde_regex")
+    )]
+    pub nested: Option<Regex>,
+}
+
+/// A multi line comment that is not ended yet
+pub(crate) struct OpenBlock<'a> {
+    /// The pattern that started the comment
+    pattern: &'a BlockPattern,
+    /// Text of the named groups in the start that must be the same in the end
+    groups: Vec<(&'a str, String)>,
+    /// Depth of nested comments
+    depth: usize,
+}
+
+/// Implementation of CommentPattern
+impl CommentPattern {
+    /// Match the single-line comment pattern in the line
+    ///
+    /// Returns None if the single-line pattern is not set or does not match
+    pub(crate) fn find_single_line<'l>(&self, line: &'l str) -> Option<Captures<'l>> {
+        self.single_line.as_ref()?.captures(line)
+    }
+
+    /// Start a multi line comment if the line matches the start of one of the patterns
+    ///
+    /// Returns the open comment and the captures of the start pattern
+    pub(crate) fn open_block<'l>(&self, line: &'l str) -> Option<(OpenBlock<'_>, Captures<'l>)> {
+        self.multi_line.iter().find_map(|pattern| {
+            let captures = pattern.start.captures(line)?;
+            let groups = pattern
+                .end
+                .capture_names()
+                .flatten()
+                .filter_map(|name| Some((name, captures.name(name)?.as_str().to_string())))
+                .collect();
+            let block = OpenBlock {
+                pattern,
+                groups,
+                depth: 1,
+            };
+            Some((block, captures))
+        })
+    }
+
+    /// Find the start of the trailing comment in the line
+    ///
+    /// The comment cannot start inside the `strings`, which are the byte ranges of the
+    /// string literals in the line found by the lexer. See [`find_string_ranges`](crate::find_string_ranges)
+    ///
+    /// Returns None if the trailing pattern is not set or does not match.
+    /// See [`CommentPattern::trailing`]
+    pub fn find_trailing(&self, line: &str, strings: &[Range<usize>]) -> Option<usize> {
+        let pattern = self.trailing.as_ref()?;
+        line.char_indices()
+            .filter(|(_, c)| !super::is_indent_char(*c))
+            .map(|(i, _)| i)
+            .filter(|i| *i == 0 || line[..*i].ends_with(super::is_indent_char))
+            .filter(|i| !strings.iter().any(|range| range.contains(i)))
+            .find(|i| pattern.is_match(&line[*i..]))
+    }
+
+    /// Get the text of a trailing comment, if the comment matches the trailing pattern
+    pub fn trailing_text<'l>(&self, comment: &'l str) -> Option<&'l str> {
+        let captures = self.trailing.as_ref()?.captures(comment)?;
+        match captures.name("text") {
+            Some(text) => Some(text.as_str()),
+            None => Some(&comment[captures.get(0)?.end()..]),
+        }
+    }
+}
+
+/// Implementation of OpenBlock
+impl OpenBlock<'_> {
+    /// If the comments can be nested
+    pub(crate) fn is_nested(&self) -> bool {
+        self.pattern.nested.is_some()
+    }
+
+    /// Find the end of the comment in the line
+    ///
+    /// Returns the start and end indices of the match of the end pattern
+    pub(crate) fn find_end(&mut self, line: &str) -> Option<(usize, usize)> {
+        let ends = self
+            .pattern
+            .end
+            .captures_iter(line)
+            .filter(|captures| {
+                self.groups
+                    .iter()
+                    .all(|(name, text)| captures.name(name).map(|m| m.as_str()) == Some(text))
+            })
+            .filter_map(|captures| captures.get(0))
+            .map(|m| (m.start(), m.end()));
+        let nested = match &self.pattern.nested {
+            Some(nested) => nested,
+            None => return ends.into_iter().next(),
+        };
+        // (start, end, is_end) of the delimiters in the order they appear
+        let mut delimiters = ends
+            .map(|(start, end)| (start, end, true))
+            .chain(nested.find_iter(line).map(|m| (m.start(), m.end(), false)))
+            .collect::<Vec<_>>();
+        delimiters.sort();
+        for (start, end, is_end) in delimiters {
+            if !is_end {
+                self.depth += 1;
+                continue;
+            }
+            self.depth -= 1;
+            if self.depth == 0 {
+                return Some((start, end));
+            }
+        }
+        None
+    }
 }
 
 /// Locate the first block of comments from list of lines
 ///
 /// Returns the start (inclusive) and end (exclusive) indices.
 ///
+/// A comment is a sequence of single line and multi line blocks, which can be mixed.
+/// The blocks can be separated by lines that match the transparent pattern.
+///
 /// The first `indent` bytes are ignored if the line starts with a space or tab
 /// . This assumes the first `indent` bytes only contains spaces/tabs
 /// (i.e. will panic if cutting off in the middle of unicode character)
@@ -35,42 +202,73 @@ pub fn find_comments(
     patterns: &CommentPattern,
     indent: usize,
 ) -> Option<(usize, usize)> {
-    let mut is_multi = false;
+    find_comments_outside_literals(body_lines, &[], patterns, indent)
+}
+
+/// Same as [`find_comments`], but the lines that start inside string literals are not comments
+///
+/// `in_literal` has one flag for each line, and missing flags are false. A line inside a literal
+/// ends the comment, unless it is inside a multi line comment.
+pub fn find_comments_outside_literals(
+    body_lines: &[String],
+    in_literal: &[bool],
+    patterns: &CommentPattern,
+    indent: usize,
+) -> Option<(usize, usize)> {
+    let mut block: Option<OpenBlock> = None;
     let mut start: Option<usize> = None;
+    // end of the last block, which excludes the transparent lines after it
+    let mut end = 0;
     for (i, line) in body_lines.iter().enumerate() {
         let line = if indent >= line.len() {
             ""
         } else {
             &line[indent..]
         };
-        match start {
-            Some(start) => {
-                if is_multi {
-                    if patterns.multi_end.is_match(line) {
-                        return Some((start, i + 1));
-                    }
-                } else if !patterns.single_line.is_match(line) {
-                    return Some((start, i));
-                }
+        if let Some(open) = &mut block {
+            if open.find_end(line).is_some() {
+                block = None;
+                end = i + 1;
+            }
+            continue;
+        }
+        if in_literal.get(i).copied().unwrap_or_default() {
+            match start {
+                Some(start) => return Some((start, end)),
+                None => continue,
             }
-            None => {
-                // not found comment yet
-                if patterns.single_line.is_match(line) {
-                    start = Some(i);
-                } else if let Some(pattern) = &patterns.multi_start {
-                    if pattern.is_match(line) {
-                        // if patterns.multi_end.is_match(line) {
-                        //     return Some((i, i + 1));
-                        // }
-                        start = Some(i);
-                        is_multi = true;
-                    }
-                }
+        }
+        if patterns.find_single_line(line).is_some() {
+            start.get_or_insert(i);
+            end = i + 1;
+        } else if let Some((mut open, captures)) = patterns.open_block(line) {
+            start.get_or_insert(i);
+            // only nested comments can end on the first line, since the end pattern
+            // may also match the start, like `"""`
+            let marker_end = captures.get(0).map_or(0, |m| m.end());
+            if open.is_nested() && open.find_end(&line[marker_end..]).is_some() {
+                end = i + 1;
+            } else {
+                block = Some(open);
+            }
+        } else if let Some(start) = start {
+            let is_transparent = patterns
+                .transparent
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(line));
+            if !is_transparent {
+                return Some((start, end));
             }
         }
     }
 
-    start.map(|start| (start, body_lines.len()))
+    // a multi line comment that is not closed continues to the end
+    let end = if block.is_some() {
+        body_lines.len()
+    } else {
+        end
+    };
+    start.map(|start| (start, end))
 }
 
 #[cfg(test)]
@@ -79,17 +277,23 @@ mod ut {
 
     fn create_test_pattern() -> CommentPattern {
         CommentPattern {
-            single_line: Regex::new(r"^///").unwrap(),
-            multi_start: Some(Regex::new(r"^/\*\*").unwrap()),
-            multi_end: Regex::new(r"\*/\s*$").unwrap(),
+            single_line: Some(Regex::new(r"^///").unwrap()),
+            multi_line: vec![BlockPattern {
+                start: Regex::new(r"^/\*\*").unwrap(),
+                end: Regex::new(r"\*/\s*$").unwrap(),
+                nested: None,
+            }],
+            transparent: None,
+            trailing: None,
         }
     }
 
     fn create_test_pattern_single_only() -> CommentPattern {
         CommentPattern {
-            single_line: Regex::new(r"^///").unwrap(),
-            multi_start: None,
-            multi_end: Regex::new(r"\*/\s*$").unwrap(),
+            single_line: Some(Regex::new(r"^///").unwrap()),
+            multi_line: vec![],
+            transparent: None,
+            trailing: None,
         }
     }
 
@@ -206,9 +410,29 @@ mod ut {
             "}".to_string(),
         ];
         let pattern = create_test_pattern();
+        // the blocks are mixed into one comment
         let (start, end) = find_comments(&lines, &pattern, 0).unwrap();
         assert_eq!(start, 0);
-        assert_eq!(end, 2);
+        assert_eq!(end, 7);
+    }
+
+    #[test]
+    fn test_mixed_with_transparent() {
+        let lines = vec![
+            "/**".to_string(),
+            " * abcde".to_string(),
+            " */".to_string(),
+            "// SAFETY: abcde".to_string(),
+            "///abcde2 ".to_string(),
+            "// SAFETY: abcde".to_string(),
+            "fn main() {".to_string(),
+            "}".to_string(),
+        ];
+        let mut pattern = create_test_pattern();
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 3)));
+        pattern.transparent = Some(Regex::new(r"^// SAFETY:").unwrap());
+        // the transparent line after the last block is not part of the comment
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 5)));
     }
 
     #[test]
@@ -361,4 +585,120 @@ mod ut {
         assert_eq!(start, 0);
         assert_eq!(end, 1);
     }
+
+    #[test]
+    fn test_multiple_pairs() {
+        let lines = vec![
+            "\"\"\"abc".to_string(),
+            "'''".to_string(),
+            "\"\"\"".to_string(),
+            "def foo():".to_string(),
+        ];
+        let pattern = CommentPattern {
+            single_line: Some(Regex::new(r#"^""".*"""\s*$|^'''.*'''\s*$"#).unwrap()),
+            multi_line: vec![
+                BlockPattern {
+                    start: Regex::new(r#"^""""#).unwrap(),
+                    end: Regex::new(r#""""\s*$"#).unwrap(),
+                    nested: None,
+                },
+                BlockPattern {
+                    start: Regex::new(r"^'''").unwrap(),
+                    end: Regex::new(r"'''\s*$").unwrap(),
+                    nested: None,
+                },
+            ],
+            transparent: None,
+            trailing: None,
+        };
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 3)));
+    }
+
+    #[test]
+    fn test_same_groups() {
+        let lines = vec![
+            "--[==[".to_string(),
+            "]]".to_string(),
+            "]=]".to_string(),
+            "]==]".to_string(),
+            "function foo()".to_string(),
+        ];
+        let pattern = CommentPattern {
+            single_line: Some(Regex::new(r"^---").unwrap()),
+            multi_line: vec![BlockPattern {
+                start: Regex::new(r"^--\[(?P<level>=*)\[").unwrap(),
+                end: Regex::new(r"\](?P<level>=*)\]").unwrap(),
+                nested: None,
+            }],
+            transparent: None,
+            trailing: None,
+        };
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 4)));
+    }
+
+    #[test]
+    fn test_nested() {
+        let lines = vec![
+            "/** a /* b".to_string(),
+            "*/ /* c */".to_string(),
+            "*/".to_string(),
+            "/** d */ /* */".to_string(),
+            "fn main() {".to_string(),
+        ];
+        let mut pattern = CommentPattern {
+            single_line: Some(Regex::new(r"^///").unwrap()),
+            multi_line: vec![BlockPattern {
+                start: Regex::new(r"^/\*\*").unwrap(),
+                end: Regex::new(r"\*/").unwrap(),
+                nested: Some(Regex::new(r"/\*").unwrap()),
+            }],
+            transparent: None,
+            trailing: None,
+        };
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 4)));
+        pattern.multi_line[0].nested = None;
+        // ends at the first end, then the next line is not a comment
+        assert_eq!(find_comments(&lines, &pattern, 0), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_literal() {
+        let lines = vec![
+            "let s = \"".to_string(),
+            "/// in string".to_string(),
+            "\";".to_string(),
+            "/// comment".to_string(),
+            "/// in string".to_string(),
+            "/** comment".to_string(),
+            "*/".to_string(),
+        ];
+        let pattern = create_test_pattern();
+        let in_literal = [false, true, false, false, true, false, true];
+        assert_eq!(
+            find_comments_outside_literals(&lines, &in_literal, &pattern, 0),
+            Some((3, 4))
+        );
+        assert_eq!(
+            find_comments_outside_literals(&lines[5..], &in_literal[5..], &pattern, 0),
+            Some((0, 2))
+        );
+    }
+
+    #[test]
+    fn test_find_trailing() {
+        let mut pattern = create_test_pattern();
+        assert_eq!(pattern.find_trailing("Red, ///< red", &[]), None);
+        pattern.trailing = Some(Regex::new(r"^///<").unwrap());
+        assert_eq!(pattern.find_trailing("Red, ///< red", &[]), Some(5));
+        assert_eq!(pattern.find_trailing("///< red", &[]), Some(0));
+        assert_eq!(pattern.find_trailing("Red,///< red", &[]), None);
+        assert_eq!(pattern.find_trailing("Red, /// red", &[]), None);
+        // the first `///<` is in a string
+        let line = r#"f(" ///< a", "b"), ///< red"#;
+        assert_eq!(pattern.find_trailing(line, &[2..11, 13..16]), Some(19));
+        assert_eq!(pattern.trailing_text("///< red"), Some(" red"));
+        pattern.trailing = Some(Regex::new(r"^/\*\*<(?P<text>.*?)(\*/\s*)?$").unwrap());
+        assert_eq!(pattern.find_trailing("Red, /**< red */", &[]), Some(5));
+        assert_eq!(pattern.trailing_text("/**< red */"), Some(" red "));
+    }
 }
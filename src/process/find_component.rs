@@ -5,6 +5,8 @@ use crate::Config;
 
 /// Result for calling find_component
 #[derive(Debug, Clone)]
+// the result is short-lived, so the size of the found component does not matter
+#[allow(clippy::large_enum_variant)]
 pub enum FindComponentResult {
     /// Found a component uniquely.
     ///
@@ -24,6 +26,9 @@ pub enum FindComponentResult {
 /// Find a component from a root component
 ///
 /// Returns the component itself if search_path is empty.
+/// The members of sections are matched together with the children, so the sections can be
+/// left out of the search path. The sections are still in the context.
+/// See [`Component::is_section`]
 pub fn find_component(
     component: &Component,
     search_path: &[String],
@@ -33,34 +38,121 @@ pub fn find_component(
         return FindComponentResult::Found(component.clone(), vec![]);
     }
 
-    let matched_children = find_children(component, &search_path[0]);
+    let matched_paths = find_child_paths(component, &search_path[0]);
 
-    match matched_children.len() {
-        0 => FindComponentResult::NotFound(search_path[0].clone()),
-        1 => {
-            let result = find_component(&matched_children[0], &search_path[1..], config);
+    match matched_paths.as_slice() {
+        [] => FindComponentResult::NotFound(search_path[0].clone()),
+        [path] => {
+            // the parents of the matched child, from the component to the innermost section
+            let mut parents = vec![component];
+            for i in &path[..path.len() - 1] {
+                parents.push(&parents[parents.len() - 1].children[*i]);
+            }
+            let matched = &parents[parents.len() - 1].children[path[path.len() - 1]];
+            let result = find_component(matched, &search_path[1..], config);
             match result {
                 FindComponentResult::Found(comp, mut ctx) => {
-                    ctx.push(Context::from_component(
-                        component,
-                        config.context_include_comments,
-                    ));
+                    let mut child = matched;
+                    for parent in parents.iter().rev() {
+                        let parent_ctx = Context::from_component(parent, child, config);
+                        if let Some(last) = ctx.last_mut() {
+                            last.merge_placeholders(&parent_ctx);
+                        }
+                        ctx.push(parent_ctx);
+                        child = parent;
+                    }
                     FindComponentResult::Found(comp, ctx)
                 }
                 _ => result,
             }
         }
-        _ => FindComponentResult::Multiple(matched_children, search_path[0].clone()),
+        _ => FindComponentResult::Multiple(
+            matched_paths
+                .iter()
+                .map(|path| child_at_path(component, path).clone())
+                .collect(),
+            search_path[0].clone(),
+        ),
     }
 }
 
+/// Find the children and the members of sections that match the search string
+///
+/// This uses the same matching rules as [`find_children`], with the members of the sections
+/// in the children matched together with the children.
+///
+/// Returns the paths of the indices from the component to each matched child,
+/// where all indices but the last one are for sections.
+pub fn find_child_paths(component: &Component, search: &str) -> Vec<Vec<usize>> {
+    let mut candidates = vec![];
+    add_candidates(component, &mut vec![], &mut candidates);
+    let max_lines = candidates
+        .iter()
+        .map(|(_, child)| child.body_lines.len())
+        .max()
+        .unwrap_or(0);
+
+    for i in 0..max_lines {
+        let matched = candidates
+            .iter()
+            .filter(|(_, child)| !child.is_section || i == 0)
+            .filter(|(_, child)| {
+                child
+                    .body_lines
+                    .get(i)
+                    .is_some_and(|line| line.contains(search))
+            })
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        if !matched.is_empty() {
+            return matched;
+        }
+    }
+
+    vec![]
+}
+
+/// Add the children of the component and the members of its sections to the candidates, with their paths
+fn add_candidates<'a>(
+    component: &'a Component,
+    path: &mut Vec<usize>,
+    candidates: &mut Vec<(Vec<usize>, &'a Component)>,
+) {
+    for (i, child) in component.children.iter().enumerate() {
+        path.push(i);
+        candidates.push((path.clone(), child));
+        if child.is_section {
+            add_candidates(child, path, candidates);
+        }
+        path.pop();
+    }
+}
+
+/// Get the descendant of the component at the path of child indices
+fn child_at_path<'a>(component: &'a Component, path: &[usize]) -> &'a Component {
+    path.iter()
+        .fold(component, |component, i| &component.children[*i])
+}
+
 /// Find children components from a component based on a search string
 ///
 /// The substring is first matched against the first line of each child.
 /// If no child is matched, it moves on to the second line, and so on.
+/// Only the first line of sections is matched, since the other lines are their members.
 ///
 /// Returns a vector of all matched children.
 pub fn find_children(component: &Component, search: &str) -> Vec<Component> {
+    find_children_indices(component, search)
+        .into_iter()
+        .map(|i| component.children[i].clone())
+        .collect()
+}
+
+/// Find the indices of children components from a component based on a search string
+///
+/// This uses the same matching rules as [`find_children`], but returns
+/// the indices into `component.children` instead of cloning the children.
+pub fn find_children_indices(component: &Component, search: &str) -> Vec<usize> {
     let max_lines = component
         .children
         .iter()
@@ -70,10 +162,13 @@ pub fn find_children(component: &Component, search: &str) -> Vec<Component> {
 
     let mut matched_children = vec![];
     for i in 0..max_lines {
-        for child in &component.children {
+        for (j, child) in component.children.iter().enumerate() {
+            if child.is_section && i > 0 {
+                continue;
+            }
             if let Some(line) = child.body_lines.get(i) {
                 if line.contains(search) {
-                    matched_children.push(child.clone());
+                    matched_children.push(j);
                 }
             }
         }
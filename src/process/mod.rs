@@ -14,13 +14,31 @@ mod find_component;
 pub use find_component::*;
 mod summarize_lines;
 pub use summarize_lines::*;
+mod ellipsis;
+pub use ellipsis::*;
+mod examples;
+pub use examples::*;
+mod find_braces;
+pub use find_braces::*;
 
 /// Helper function to check if a char is a valid indent character
 pub fn is_indent_char(c: char) -> bool {
     c == ' ' || c == '\t'
 }
 
-/// Helper function to indent a string
-pub fn indent_string(s: &str, indent: usize) -> String {
-    format!("{:indent$}{s}", "")
+/// Helper function to indent a string with the characters of the indentation, like a tab
+pub fn indent_string(s: &str, indent: &str) -> String {
+    format!("{indent}{s}")
+}
+
+/// Find the characters of the indentation of the lines, like four spaces or a tab
+///
+/// The characters are taken from the first line that is indented by at least `indent` characters,
+/// or are spaces if there is no such line.
+pub fn find_indent_prefix(lines: &[String], indent: usize) -> String {
+    lines
+        .iter()
+        .filter_map(|line| line.get(..indent))
+        .find(|prefix| prefix.chars().all(is_indent_char))
+        .map_or_else(|| " ".repeat(indent), str::to_string)
 }
@@ -1,29 +1,185 @@
 //! Logic and data structures for parsing/finding a component from lines
 
-use crate::process::{find_comments, find_indentation, unindent_lines, CommentPattern};
-use crate::Config;
+use std::collections::HashSet;
+
+use crate::process::{
+    find_brace_block, find_comments_outside_literals, find_indent_prefix, find_indentation,
+    find_signature, find_statement_end, render_summary_with_kinds, scan_braces, scan_brackets,
+    summarize_block_line_ranges, summarize_line_ranges_by, unindent_lines_keeping, Ellipsis,
+    Nesting, SummaryLine,
+};
+use crate::{find_grammar, find_string_lines, find_string_ranges, Config, LineKind};
+use regex::Regex;
 
 /// Data of a component
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Component {
     /// If the component is the root file
     pub is_root: bool,
     /// Outer comments
     pub outer_comments: Vec<String>,
+    /// Attribute lines between the outer comments and the body, like `#[derive(Debug)]` or `@Override`
+    ///
+    /// See [`Config::attribute_pattern`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub attributes: Vec<String>,
     /// Body lines (unparsed)
     ///
     /// Includes inner comment lines
     pub body_lines: Vec<String>,
+    /// Line numbers of the body lines in the file, starting from 1
+    pub body_line_numbers: Vec<usize>,
+    /// Range of line numbers of the component in the file, including the outer comments
+    ///
+    /// The range has inclusive start and exclusive end.
+    pub span: (usize, usize),
     /// Inner comments
     ///
     /// These are unindented
     pub inner_comments: Vec<String>,
     /// Range of lines for inner comments in the body lines
     pub inner_comments_range: Option<(usize, usize)>,
+    /// Trailing comments in the body lines, like `///< the red channel` in `Red, ///< the red channel`
+    ///
+    /// See [`CommentPattern::trailing`](crate::process::CommentPattern::trailing)
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub trailing_comments: Vec<String>,
     /// Child components
     pub children: Vec<Component>,
     /// Indentation from parent
     pub indent: usize,
+    /// Characters of the indentation from parent, like four spaces or a tab
+    ///
+    /// Used to indent the children and the placeholders when printing
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub indent_prefix: String,
+    /// Range of lines inside the braces in the body lines, with brace nesting
+    ///
+    /// If set, the lines in the range are elided in the summary instead of the indented lines
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub block: Option<(usize, usize)>,
+    /// Range of the lines of the signature in the body lines, if it has more than one line
+    ///
+    /// The indented lines in the range are kept in the summary. See [`find_signature`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub signature: Option<(usize, usize)>,
+    /// Indices of the body lines that start inside string literals
+    ///
+    /// These lines are elided in the summary like indented lines. See [`Config::lexer`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub literal_lines: Vec<usize>,
+    /// If the component is a section that groups the components after a section or region marker
+    ///
+    /// The first body line is the marker, and the members are in the block.
+    /// See [`Config::section_pattern`]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub is_section: bool,
+}
+
+/// Implementation of Component
+impl Component {
+    /// Render the body lines in summary view, with the inner comments kept
+    ///
+    /// Sections are rendered as an outline. See [`Component::outline`]
+    pub fn summarize(&self, ellipsis: &Ellipsis) -> Vec<String> {
+        self.summarize_with_kinds(ellipsis)
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect()
+    }
+
+    /// Render the body lines in summary view, with the kind of each line
+    ///
+    /// See [`Component::summarize`] and [`render_summary_with_kinds`]
+    pub fn summarize_with_kinds(&self, ellipsis: &Ellipsis) -> Vec<(LineKind, String)> {
+        if self.is_section {
+            return self
+                .outline()
+                .into_iter()
+                .map(|line| (LineKind::Target, line))
+                .collect();
+        }
+        render_summary_with_kinds(
+            &self.summarize_body(0, self.body_lines.len(), self.inner_comments_range),
+            &self.body_lines,
+            &self.body_line_numbers,
+            &self.indent_prefix,
+            ellipsis,
+        )
+    }
+
+    /// Render a section as an outline, with the first body line of each member
+    ///
+    /// The marker of the section and the end marker of the region are kept
+    pub fn outline(&self) -> Vec<String> {
+        let (members_start, members_end) = self.block.unwrap_or((0, self.body_lines.len()));
+        let members = self
+            .children
+            .iter()
+            .filter_map(|child| child.body_lines.first())
+            .map(|line| super::indent_string(line, &self.indent_prefix));
+        self.body_lines[..members_start]
+            .iter()
+            .cloned()
+            .chain(members)
+            .chain(self.body_lines[members_end..].iter().cloned())
+            .collect()
+    }
+
+    /// Convert the body lines in the range to summary view
+    ///
+    /// The indices in the summary and the exclude range are relative to the start of the range.
+    /// The indented lines and the lines inside string literals are elided, except for the lines
+    /// of the signature. With brace nesting, the lines in the block are elided instead.
+    /// See [`summarize_line_ranges_by`] and [`summarize_block_line_ranges`]
+    pub fn summarize_body(
+        &self,
+        start: usize,
+        end: usize,
+        exclude: Option<(usize, usize)>,
+    ) -> Vec<SummaryLine> {
+        let lines = &self.body_lines[start..end];
+        match self.block {
+            Some((block_start, block_end)) => summarize_block_line_ranges(
+                lines,
+                exclude,
+                (
+                    block_start.saturating_sub(start),
+                    block_end.saturating_sub(start),
+                ),
+            ),
+            None => summarize_line_ranges_by(lines, exclude, |i, line| {
+                let i = start + i;
+                let in_signature =
+                    self.signature
+                        .is_some_and(|(signature_start, signature_end)| {
+                            i >= signature_start && i < signature_end
+                        });
+                !in_signature
+                    && (line.starts_with(super::is_indent_char)
+                        || self.literal_lines.binary_search(&i).is_ok())
+            }),
+        }
+    }
 }
 
 /// Parse a component body.
@@ -32,99 +188,804 @@ pub struct Component {
 /// stored directly in the Component.
 /// The indent is used to process the lines, and the parsing is done on the
 /// unindented lines.
+///
+/// The body line numbers are the line numbers of the body lines in the file,
+/// and the start line is the line number of the first line of the component
+/// (including the outer comments).
+///
+/// If the lexer is set in the config, the lines inside string literals are never
+/// comments or the boundaries of components.
 pub fn parse_component(
     outer_comments: Vec<String>,
     body_lines: Vec<String>,
+    body_line_numbers: Vec<usize>,
+    start_line: usize,
+    indent: usize,
+    is_root: bool,
+    config: &Config,
+) -> Component {
+    let literal_lines = find_literal_lines(&body_lines, &body_line_numbers, config);
+    if config.nesting == Nesting::Brace {
+        return parse_brace_component(
+            outer_comments,
+            body_lines,
+            body_line_numbers,
+            start_line,
+            is_root,
+            &literal_lines,
+            config,
+        );
+    }
+    parse_indent_component(
+        outer_comments,
+        body_lines,
+        body_line_numbers,
+        start_line,
+        indent,
+        is_root,
+        &literal_lines,
+        config,
+    )
+}
+
+/// Parse a component body with indent nesting
+///
+/// Same as [`parse_component`], with the line numbers of the lines inside string literals
+#[allow(clippy::too_many_arguments)]
+fn parse_indent_component(
+    outer_comments: Vec<String>,
+    body_lines: Vec<String>,
+    body_line_numbers: Vec<usize>,
+    start_line: usize,
     indent: usize,
     is_root: bool,
+    literal_lines: &HashSet<usize>,
     config: &Config,
 ) -> Component {
+    let span = (
+        start_line,
+        body_line_numbers.last().map_or(start_line, |end| end + 1),
+    );
+    let signature = find_multi_line_signature(&body_lines, is_root, config);
+    let in_literal = body_line_numbers
+        .iter()
+        .map(|n| literal_lines.contains(n))
+        .collect::<Vec<_>>();
     // need to find indent range first since the range will be
     // different when unindented
-    let inner_comments_range = find_comments(&body_lines, &config.inner_comments, indent);
-    let unindented_body_lines = unindent_lines(&body_lines, indent)
-        .into_iter()
-        .filter(|line| {
-            !config
-                .ignore_lines
-                .iter()
-                .any(|pattern| pattern.is_match(line))
-        })
+    let inner_comments_range =
+        find_comments_outside_literals(&body_lines, &in_literal, &config.inner_comments, indent);
+    let (unindented_indices, unindented_body_lines): (Vec<_>, Vec<_>) =
+        unindent_lines_keeping(&body_lines, indent, |i| in_literal[i])
+            .into_iter()
+            .filter(|(i, line)| {
+                in_literal[*i]
+                    || !config
+                        .ignore_lines
+                        .iter()
+                        .any(|pattern| pattern.is_match(line))
+            })
+            .unzip();
+    let unindented_line_numbers = unindented_indices
+        .iter()
+        .map(|i| body_line_numbers[*i])
+        .collect::<Vec<_>>();
+    let unindented_in_literal = unindented_indices
+        .iter()
+        .map(|i| in_literal[*i])
         .collect::<Vec<_>>();
+    let literal_indices = literal_line_indices(&in_literal);
+    // children are in the indented blocks, except for the root where they are not indented.
+    // A line that is not indented ends the block, like the closing bracket of the component.
+    // Those lines are removed when unindenting, so check the lines skipped before line `i`
+    let ends_block = |i: usize| {
+        !is_root
+            && body_lines[unindented_indices[i - 1] + 1..unindented_indices[i]]
+                .iter()
+                .any(|line| !line.is_empty() && !line.starts_with(super::is_indent_char))
+    };
 
     let mut comment_end = 0;
 
     // find inner comments
-    let inner_comments = if let Some((start, end)) =
-        find_comments(&unindented_body_lines, &config.inner_comments, 0)
-    {
+    let inner_comments = if let Some((start, end)) = find_comments_outside_literals(
+        &unindented_body_lines,
+        &unindented_in_literal,
+        &config.inner_comments,
+        0,
+    ) {
         comment_end = end;
         unindented_body_lines[start..end].to_vec()
     } else {
         vec![]
     };
 
-    // skip to the first child
-    let (mut comment_start, mut comment_end) = if let Some((start, end)) =
-        find_next_child_outer_comment(
-            &unindented_body_lines[comment_end..],
-            &config.outer_comments,
-        ) {
-        (comment_end + start, comment_end + end)
-    } else {
-        // no children
-        return Component {
-            is_root,
-            outer_comments,
-            body_lines,
-            inner_comments,
-            inner_comments_range,
-            children: vec![],
-            indent,
-        };
-    };
+    // find the children, which start at outer comments or at lines with trailing comments
+    let children_start = comment_end;
+    let mut outer_children = vec![];
+    while let Some((start, end)) = find_next_child_outer_comment(
+        &unindented_body_lines[comment_end..],
+        &unindented_in_literal[comment_end..],
+        config,
+    ) {
+        outer_children.push((comment_end + start, comment_end + end));
+        comment_end += end;
+    }
+    // without indented lines, the lines of a child are its own declaration
+    let child_starts = find_child_starts(
+        &unindented_body_lines,
+        children_start,
+        outer_children,
+        |i| {
+            (is_root || indent > 0)
+                && !unindented_in_literal[i]
+                && !unindented_body_lines[i].starts_with(super::is_indent_char)
+        },
+        !is_root && is_function(&body_lines, config),
+        config,
+    );
 
     let mut children = vec![];
-
-    while comment_end < unindented_body_lines.len() {
+    for (i, child_start) in child_starts.iter().enumerate() {
+        let (comment_start, comment_end) = match child_start {
+            ChildStart::Outer(start, end) => (*start, *end),
+            ChildStart::Trailing(trailing) => {
+                children
+                    .push(trailing.to_component(&unindented_body_lines, &unindented_line_numbers));
+                continue;
+            }
+            ChildStart::Marker(_, _) => continue,
+        };
         // extract child lines
         let child_outer_comments = unindented_body_lines[comment_start..comment_end].to_vec();
-        // try finding next comment
-        let child_body_lines = if let Some((start, end)) = find_next_child_outer_comment(
-            &unindented_body_lines[comment_end..],
-            &config.outer_comments,
-        ) {
-            let child_body_lines = unindented_body_lines[comment_end..comment_end + start].to_vec();
-            // update indices
-            comment_start = comment_end + start;
-            comment_end += end;
-            child_body_lines
-        } else {
-            let child_body_lines = unindented_body_lines[comment_end..].to_vec();
-            comment_end = unindented_body_lines.len();
-            child_body_lines
-        };
+        let child_start_line = unindented_line_numbers[comment_start];
+        let body_end = child_starts
+            .get(i + 1)
+            .map_or(unindented_body_lines.len(), ChildStart::start);
+        let body_end = (comment_end + 1..body_end)
+            .find(|i| ends_block(*i))
+            .unwrap_or(body_end);
+        let mut child_body_lines = unindented_body_lines[comment_end..body_end].to_vec();
+        let mut child_body_line_numbers = unindented_line_numbers[comment_end..body_end].to_vec();
+        let attribute_count = count_attribute_lines(&child_body_lines, config);
+        let attributes = child_body_lines.drain(..attribute_count).collect();
+        child_body_line_numbers.drain(..attribute_count);
         let child_indent = find_indentation(&child_body_lines);
-        children.push(parse_component(
+        let mut child = parse_indent_component(
             child_outer_comments,
             child_body_lines,
+            child_body_line_numbers,
+            child_start_line,
             child_indent,
             false,
+            literal_lines,
+            config,
+        );
+        child.attributes = attributes;
+        children.push(child);
+    }
+    let children = group_sections(
+        children,
+        &child_starts,
+        &unindented_body_lines,
+        &unindented_line_numbers,
+    );
+
+    let indent_prefix = find_indent_prefix(&body_lines, indent);
+    Component {
+        is_root,
+        outer_comments,
+        body_lines,
+        body_line_numbers,
+        span,
+        inner_comments,
+        inner_comments_range,
+        trailing_comments: vec![],
+        children,
+        indent_prefix,
+        indent,
+        block: None,
+        attributes: vec![],
+        signature,
+        literal_lines: literal_indices,
+        is_section: false,
+    }
+}
+
+/// Parse a component body with brace nesting
+///
+/// Same as [`parse_component`], but the children are found in the lines inside the braces
+/// of the component, regardless of the indentation. See [`find_block_indentation`] for
+/// the indentation of the component.
+fn parse_brace_component(
+    outer_comments: Vec<String>,
+    body_lines: Vec<String>,
+    body_line_numbers: Vec<usize>,
+    start_line: usize,
+    is_root: bool,
+    literal_lines: &HashSet<usize>,
+    config: &Config,
+) -> Component {
+    let span = (
+        start_line,
+        body_line_numbers.last().map_or(start_line, |end| end + 1),
+    );
+    let signature = find_multi_line_signature(&body_lines, is_root, config);
+    let block = if is_root {
+        None
+    } else {
+        find_brace_block(&body_lines, has_lifetimes(config))
+    };
+    let (block_start, block_end) = match (is_root, block) {
+        (true, _) => (0, body_lines.len()),
+        (false, Some(block)) => block,
+        (false, None) => {
+            // nothing to elide without lines inside braces
+            let indent = find_indentation(&body_lines);
+            return Component {
+                is_root,
+                outer_comments,
+                indent,
+                indent_prefix: find_indent_prefix(&body_lines, indent),
+                block: Some((body_lines.len(), body_lines.len())),
+                body_lines,
+                body_line_numbers,
+                span,
+                inner_comments: vec![],
+                inner_comments_range: None,
+                trailing_comments: vec![],
+                children: vec![],
+                attributes: vec![],
+                signature,
+                literal_lines: vec![],
+                is_section: false,
+            };
+        }
+    };
+    let block_lines = &body_lines[block_start..block_end];
+    let indent = find_block_indentation(block_lines, has_lifetimes(config));
+    let indent_prefix = find_indent_prefix(block_lines, indent);
+    let (indices, lines): (Vec<_>, Vec<_>) = block_lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line_indent = line.len() - line.trim_start_matches(super::is_indent_char).len();
+            (block_start + i, line[line_indent.min(indent)..].to_string())
+        })
+        .filter(|(i, line)| {
+            literal_lines.contains(&body_line_numbers[*i])
+                || !config
+                    .ignore_lines
+                    .iter()
+                    .any(|pattern| pattern.is_match(line))
+        })
+        .unzip();
+    let line_numbers = indices
+        .iter()
+        .map(|i| body_line_numbers[*i])
+        .collect::<Vec<_>>();
+    let in_literal = line_numbers
+        .iter()
+        .map(|n| literal_lines.contains(n))
+        .collect::<Vec<_>>();
+
+    // find inner comments
+    let mut comment_end = 0;
+    let (inner_comments, inner_comments_range) =
+        match find_comments_outside_literals(&lines, &in_literal, &config.inner_comments, 0) {
+            Some((start, end)) => {
+                comment_end = end;
+                let range_end = indices.get(end).map_or(block_end, |i| *i);
+                (
+                    lines[start..end].to_vec(),
+                    Some((indices[start], range_end)),
+                )
+            }
+            None => (vec![], None),
+        };
+
+    // find the children, which are the statements after outer comments at depth 0
+    let braces = scan_braces(&lines, has_lifetimes(config));
+    let depth_at = |i: usize| if i == 0 { 0 } else { braces[i - 1].0 };
+    let find_next_child = |from: usize| {
+        let mut from = from;
+        while let Some((start, end)) = find_comments_outside_literals(
+            &lines[from..],
+            &in_literal[from..],
+            &config.outer_comments,
+            0,
+        ) {
+            let (start, end) = (from + start, from + end);
+            if depth_at(start) == 0
+                && lines.get(end).is_some_and(|line| !line.is_empty())
+                && is_adjacent_to_declaration(&lines[end..], config)
+            {
+                return Some((start, end));
+            }
+            from = end;
+        }
+        None
+    };
+    let mut outer_children = vec![];
+    let mut next_child = find_next_child(comment_end);
+    while let Some((start, end)) = next_child {
+        outer_children.push((start, end));
+        // comments inside the braces of the child are not at depth 0
+        next_child = find_next_child(end);
+    }
+    let child_starts = find_child_starts(
+        &lines,
+        comment_end,
+        outer_children,
+        |i| !in_literal[i] && depth_at(i) == 0,
+        !is_root && is_function(&body_lines, config),
+        config,
+    );
+    let mut children = vec![];
+    for (i, child_start) in child_starts.iter().enumerate() {
+        let (comment_start, comment_end) = match child_start {
+            ChildStart::Outer(start, end) => (*start, *end),
+            ChildStart::Trailing(trailing) => {
+                children.push(trailing.to_component(&lines, &line_numbers));
+                continue;
+            }
+            ChildStart::Marker(_, _) => continue,
+        };
+        // the braces in attributes are not the braces of the child
+        let body_start = comment_end + count_attribute_lines(&lines[comment_end..], config);
+        let statement_end = find_statement_end(&lines, &braces, body_start);
+        let body_end = child_starts
+            .get(i + 1)
+            .map_or(statement_end, |next| statement_end.min(next.start()));
+        let body_start = body_start.min(body_end);
+        let mut child = parse_brace_component(
+            lines[comment_start..comment_end].to_vec(),
+            lines[body_start..body_end].to_vec(),
+            line_numbers[body_start..body_end].to_vec(),
+            line_numbers[comment_start],
+            false,
+            literal_lines,
             config,
-        ));
+        );
+        child.attributes = lines[comment_end..body_start].to_vec();
+        children.push(child);
     }
+    let children = group_sections(children, &child_starts, &lines, &line_numbers);
 
     Component {
         is_root,
         outer_comments,
         body_lines,
+        body_line_numbers,
+        span,
         inner_comments,
         inner_comments_range,
+        trailing_comments: vec![],
         children,
         indent,
+        indent_prefix,
+        block,
+        attributes: vec![],
+        signature,
+        literal_lines: vec![],
+        is_section: false,
+    }
+}
+
+/// Start of a child component in the lines of the parent
+enum ChildStart {
+    /// A child with outer comments, by the start and end indices of the outer comments
+    Outer(usize, usize),
+    /// A child with trailing comments
+    Trailing(TrailingChild),
+    /// A line that starts or ends a section, by its index
+    Marker(usize, Marker),
+}
+
+/// Kind of a line that starts or ends a section
+///
+/// See [`Config::section_pattern`] and [`Config::region_start_pattern`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Marker {
+    /// Starts a section that ends at the next section or region
+    Section,
+    /// Starts a region that ends at the matching end marker
+    RegionStart,
+    /// Ends the innermost region
+    RegionEnd,
+}
+
+/// Implementation of ChildStart
+impl ChildStart {
+    /// Index of the first line of the child
+    fn start(&self) -> usize {
+        match self {
+            ChildStart::Outer(start, _) => *start,
+            ChildStart::Trailing(trailing) => trailing.start,
+            ChildStart::Marker(start, _) => *start,
+        }
+    }
+}
+
+/// A child component documented by trailing comments, like `Red, ///< the red channel`
+///
+/// See [`CommentPattern::trailing`](crate::process::CommentPattern::trailing)
+struct TrailingChild {
+    /// Index of the line of the code
+    start: usize,
+    /// Index after the last line, including the trailing comments on their own lines
+    end: usize,
+    /// The trailing comments
+    comments: Vec<String>,
+}
+
+/// Implementation of TrailingChild
+impl TrailingChild {
+    /// Convert to a component without children
+    ///
+    /// The lines are kept unchanged as the body lines, with the trailing comments in them
+    fn to_component(&self, lines: &[String], line_numbers: &[usize]) -> Component {
+        Component {
+            is_root: false,
+            outer_comments: vec![],
+            attributes: vec![],
+            body_lines: lines[self.start..self.end].to_vec(),
+            body_line_numbers: line_numbers[self.start..self.end].to_vec(),
+            span: (line_numbers[self.start], line_numbers[self.end - 1] + 1),
+            inner_comments: vec![],
+            inner_comments_range: None,
+            trailing_comments: self.comments.clone(),
+            children: vec![],
+            indent: 0,
+            indent_prefix: String::new(),
+            block: None,
+            signature: None,
+            literal_lines: vec![],
+            is_section: false,
+        }
+    }
+}
+
+/// If the first line is the signature of a function, matching [`Config::function_pattern`]
+fn is_function(lines: &[String], config: &Config) -> bool {
+    let pattern = match &config.function_pattern {
+        Some(pattern) => pattern,
+        None => return false,
+    };
+    lines.first().is_some_and(|line| pattern.is_match(line))
+}
+
+/// Find the children with trailing comments or without comments, and merge them with the children
+/// with outer comments
+///
+/// The trailing comments and the items are searched from the `from` index, in the lines
+/// where `is_code` is true, like the lines that are not indented. The outer comments are not searched.
+/// If the first line of a child with outer comments has a trailing comment, the outer comments
+/// are used, unless they are only empty lines like with `--outer '^$'`.
+/// An item only starts a child if it has no outer or trailing comments. If `in_function` is true,
+/// only the items that are functions are searched, so local variables are not children.
+/// See [`Config::item_pattern`] and [`is_function`]
+///
+/// The section and region markers that are not in the outer comments are also returned,
+/// so they end the body of the child before them. See [`group_sections`]
+fn find_child_starts<F>(
+    lines: &[String],
+    from: usize,
+    mut outer_children: Vec<(usize, usize)>,
+    is_code: F,
+    in_function: bool,
+    config: &Config,
+) -> Vec<ChildStart>
+where
+    F: Fn(usize) -> bool,
+{
+    let in_outer_comments = |i: usize| {
+        outer_children
+            .iter()
+            .any(|(start, end)| i >= *start && i < *end)
+    };
+    let strings = match config.lexer.as_deref().and_then(find_grammar) {
+        Some(grammar) => find_string_ranges(lines, grammar),
+        None => vec![],
+    };
+    let mut trailing_children: Vec<TrailingChild> = vec![];
+    for (i, line) in lines.iter().enumerate().skip(from) {
+        if !is_code(i) || in_outer_comments(i) {
+            continue;
+        }
+        let line_strings = strings.get(i).map_or(&[][..], Vec::as_slice);
+        let comment_start = match config.outer_comments.find_trailing(line, line_strings) {
+            Some(start) => start,
+            None => continue,
+        };
+        let comment = line[comment_start..].to_string();
+        if !line[..comment_start].trim().is_empty() {
+            trailing_children.push(TrailingChild {
+                start: i,
+                end: i + 1,
+                comments: vec![comment],
+            });
+            continue;
+        }
+        // a trailing comment on its own line is for the line above
+        match trailing_children.last_mut() {
+            Some(last) if last.end == i => {
+                last.end += 1;
+                last.comments.push(comment);
+            }
+            _ if i > from
+                && is_code(i - 1)
+                && !in_outer_comments(i - 1)
+                && !lines[i - 1].trim().is_empty() =>
+            {
+                trailing_children.push(TrailingChild {
+                    start: i - 1,
+                    end: i + 1,
+                    comments: vec![comment],
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let is_empty_comment =
+        |(start, end): (usize, usize)| lines[start..end].iter().all(|line| line.trim().is_empty());
+    trailing_children.retain(|trailing| {
+        outer_children
+            .iter()
+            .all(|outer| outer.1 != trailing.start || is_empty_comment(*outer))
+    });
+    outer_children.retain(|outer| {
+        trailing_children
+            .iter()
+            .all(|trailing| trailing.start != outer.1)
+    });
+
+    // undocumented items start at their attributes, with no outer comments
+    let mut items = vec![];
+    if let Some(pattern) = &config.item_pattern {
+        let mut i = from;
+        while i < lines.len() {
+            if !is_code(i) {
+                i += 1;
+                continue;
+            }
+            let item = i + count_attribute_lines(&lines[i..], config);
+            let is_documented = outer_children.iter().any(|(_, end)| *end == i)
+                || trailing_children.iter().any(|trailing| trailing.start == i);
+            let in_outer_comments = outer_children
+                .iter()
+                .any(|(start, end)| i >= *start && i < *end);
+            if !is_documented
+                && !in_outer_comments
+                && item < lines.len()
+                && is_code(item)
+                && pattern.is_match(&lines[item])
+                && (!in_function || is_function(&lines[item..], config))
+            {
+                items.push((i, i));
+            }
+            i = item + 1;
+        }
+    }
+
+    // section markers are comments that are not the outer comments of a child
+    let mut markers = vec![];
+    for (i, line) in lines.iter().enumerate().skip(from) {
+        let in_outer_comments = outer_children
+            .iter()
+            .any(|(start, end)| i >= *start && i < *end);
+        if !is_code(i) || in_outer_comments {
+            continue;
+        }
+        if let Some(marker) = find_marker(line, config) {
+            markers.push(ChildStart::Marker(i, marker));
+        }
+    }
+
+    let mut child_starts = outer_children
+        .into_iter()
+        .chain(items)
+        .map(|(start, end)| ChildStart::Outer(start, end))
+        .chain(trailing_children.into_iter().map(ChildStart::Trailing))
+        .chain(markers)
+        .collect::<Vec<_>>();
+    child_starts.sort_by_key(ChildStart::start);
+    child_starts
+}
+
+/// Find the kind of section marker on the line, if any
+///
+/// The end of a region is checked first, in case the start pattern also matches it
+fn find_marker(line: &str, config: &Config) -> Option<Marker> {
+    let is_match = |pattern: &Option<Regex>| pattern.as_ref().is_some_and(|p| p.is_match(line));
+    if is_match(&config.region_end_pattern) {
+        Some(Marker::RegionEnd)
+    } else if is_match(&config.region_start_pattern) {
+        Some(Marker::RegionStart)
+    } else if is_match(&config.section_pattern) {
+        Some(Marker::Section)
+    } else {
+        None
+    }
+}
+
+/// Group the children into sections at the markers in the child starts
+///
+/// A section starts at a section marker and ends at the next section or region start marker, at
+/// the end marker of the region it is in, or at the end of the lines. A region starts at a region
+/// start marker and ends after the matching end marker, or at the end of the lines. Regions can be
+/// nested, and sections can be in regions. An end marker without a region is ignored.
+///
+/// The lines and line numbers are the ones the child starts are for
+fn group_sections(
+    children: Vec<Component>,
+    child_starts: &[ChildStart],
+    lines: &[String],
+    line_numbers: &[usize],
+) -> Vec<Component> {
+    let markers = child_starts
+        .iter()
+        .filter_map(|child_start| match child_start {
+            ChildStart::Marker(i, marker) => Some((*i, *marker)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if markers.is_empty() {
+        return children;
+    }
+    // the open sections and regions from the outermost, by the index of the marker
+    let mut open: Vec<(usize, Marker, Vec<Component>)> = vec![];
+    let mut grouped = vec![];
+    let push = |open: &mut Vec<(usize, Marker, Vec<Component>)>,
+                grouped: &mut Vec<Component>,
+                child: Component| match open.last_mut() {
+        Some((_, _, members)) => members.push(child),
+        None => grouped.push(child),
+    };
+    let mut children = children.into_iter().peekable();
+    for (i, marker) in markers {
+        while let Some(child) = children.next_if(|child| child.span.0 < line_numbers[i]) {
+            push(&mut open, &mut grouped, child);
+        }
+        match marker {
+            Marker::Section | Marker::RegionStart => {
+                // a section ends at the next marker at the same level
+                if let Some((start, Marker::Section, _)) = open.last() {
+                    let start = *start;
+                    let (_, _, members) = open.pop().unwrap();
+                    let section = section_component(lines, line_numbers, start, i, false, members);
+                    push(&mut open, &mut grouped, section);
+                }
+                open.push((i, marker, vec![]));
+            }
+            Marker::RegionEnd => {
+                if !open.iter().any(|(_, m, _)| *m == Marker::RegionStart) {
+                    continue;
+                }
+                // close the sections in the region, then the region with the end marker
+                while let Some((start, m, members)) = open.pop() {
+                    let is_region = m == Marker::RegionStart;
+                    let end = if is_region { i + 1 } else { i };
+                    let section =
+                        section_component(lines, line_numbers, start, end, is_region, members);
+                    push(&mut open, &mut grouped, section);
+                    if is_region {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    for child in children {
+        push(&mut open, &mut grouped, child);
+    }
+    while let Some((start, _, members)) = open.pop() {
+        let section = section_component(lines, line_numbers, start, lines.len(), false, members);
+        push(&mut open, &mut grouped, section);
+    }
+
+    grouped
+}
+
+/// Create a section component from the lines between the start and end indices
+///
+/// The first line is the marker, and the last line is the end marker if `has_end_marker` is true.
+/// The lines in between are the block, so they are elided in the summary like braces
+fn section_component(
+    lines: &[String],
+    line_numbers: &[usize],
+    start: usize,
+    end: usize,
+    has_end_marker: bool,
+    children: Vec<Component>,
+) -> Component {
+    let body_lines = lines[start..end].to_vec();
+    let members_end = if has_end_marker {
+        body_lines.len() - 1
+    } else {
+        body_lines.len()
+    };
+    Component {
+        is_root: false,
+        outer_comments: vec![],
+        attributes: vec![],
+        block: Some((1, members_end.max(1))),
+        body_lines,
+        body_line_numbers: line_numbers[start..end].to_vec(),
+        span: (line_numbers[start], line_numbers[end - 1] + 1),
+        inner_comments: vec![],
+        inner_comments_range: None,
+        trailing_comments: vec![],
+        children,
+        indent: 0,
+        indent_prefix: String::new(),
+        signature: None,
+        literal_lines: vec![],
+        is_section: true,
     }
 }
 
+/// Find the signature of a component if it has more than one line
+///
+/// The root component does not have a signature
+fn find_multi_line_signature(
+    body_lines: &[String],
+    is_root: bool,
+    config: &Config,
+) -> Option<(usize, usize)> {
+    if is_root {
+        return None;
+    }
+    find_signature(
+        body_lines,
+        config.signature_end.as_ref(),
+        has_lifetimes(config),
+    )
+    .filter(|(start, end)| end - start > 1)
+}
+
+/// Check if `'` can start a lifetime or label like `'a`, which is when the grammar
+/// of the lexer has character literals, like Rust
+fn has_lifetimes(config: &Config) -> bool {
+    config
+        .lexer
+        .as_deref()
+        .and_then(find_grammar)
+        .is_some_and(|grammar| grammar.char_literals)
+}
+
+/// Find the line numbers of the body lines that start inside string literals
+///
+/// The lines are lexed with the grammar of the lexer in the config.
+/// Empty if the lexer is not set
+fn find_literal_lines(
+    body_lines: &[String],
+    body_line_numbers: &[usize],
+    config: &Config,
+) -> HashSet<usize> {
+    let grammar = match config.lexer.as_deref().and_then(find_grammar) {
+        Some(grammar) => grammar,
+        None => return HashSet::new(),
+    };
+    find_string_lines(body_lines, grammar)
+        .into_iter()
+        .zip(body_line_numbers)
+        .filter(|(in_literal, _)| *in_literal)
+        .map(|(_, line_number)| *line_number)
+        .collect()
+}
+
+/// Get the indices of the lines that start inside string literals
+fn literal_line_indices(in_literal: &[bool]) -> Vec<usize> {
+    in_literal
+        .iter()
+        .enumerate()
+        .filter(|(_, in_literal)| **in_literal)
+        .map(|(i, _)| i)
+        .collect()
+}
+
 /// Helper for locating the next child's outer comments.
 ///
 /// Returns the start and end indices of the next child's outer comments,
@@ -132,17 +993,28 @@ pub fn parse_component(
 ///
 /// The criteria for a child is that it has outer comments, and the next line
 /// after the outer comment exists, is not empty, and has no indent.
+/// In adjacency mode, it must also be a declaration. See [`is_adjacent_to_declaration`].
+/// The lines inside string literals are not comments.
 fn find_next_child_outer_comment(
     lines: &[String],
-    pattern: &CommentPattern,
+    in_literal: &[bool],
+    config: &Config,
 ) -> Option<(usize, usize)> {
     let mut comment_end = 0;
     while comment_end < lines.len() {
         // find next outer comment from comment_end
-        if let Some((start, end)) = find_comments(&lines[comment_end..], pattern, 0) {
+        if let Some((start, end)) = find_comments_outside_literals(
+            &lines[comment_end..],
+            &in_literal[comment_end..],
+            &config.outer_comments,
+            0,
+        ) {
             // check if next line is not empty and not indented
             if let Some(next_line) = lines.get(comment_end + end) {
-                if !next_line.is_empty() && !next_line.starts_with(super::is_indent_char) {
+                if !next_line.is_empty()
+                    && !next_line.starts_with(super::is_indent_char)
+                    && is_adjacent_to_declaration(&lines[comment_end + end..], config)
+                {
                     // found next child
                     return Some((comment_end + start, comment_end + end));
                 }
@@ -157,3 +1029,77 @@ fn find_next_child_outer_comment(
     // outer comment not found
     None
 }
+
+/// Check if the lines after outer comments start with a declaration, in adjacency mode
+///
+/// The attribute lines are skipped. Always true if not in adjacency mode,
+/// or if the declaration pattern is not set.
+fn is_adjacent_to_declaration(lines: &[String], config: &Config) -> bool {
+    let pattern = match &config.declaration_pattern {
+        Some(pattern) if config.adjacent => pattern,
+        _ => return true,
+    };
+    lines
+        .get(count_attribute_lines(lines, config))
+        .is_some_and(|line| pattern.is_match(line.trim_start()))
+}
+
+/// Find the indentation of the lines inside the braces of a component
+///
+/// This is the smallest indentation of the non-empty lines at depth 0, ignoring labels
+/// like `public:` that are usually less indented. Lines inside nested braces are not considered.
+fn find_block_indentation(lines: &[String], lifetimes: bool) -> usize {
+    let braces = scan_braces(lines, lifetimes);
+    let indentations = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| {
+            let depth = if *i == 0 { 0 } else { braces[i - 1].0 };
+            depth == 0 && !line.trim().is_empty()
+        })
+        .map(|(_, line)| {
+            let indent = line.len() - line.trim_start_matches(super::is_indent_char).len();
+            (indent, line.trim_end().ends_with(':'))
+        })
+        .collect::<Vec<_>>();
+    let min_indentation = |include_labels: bool| {
+        indentations
+            .iter()
+            .filter(|(_, is_label)| include_labels || !is_label)
+            .map(|(indent, _)| *indent)
+            .min()
+    };
+    min_indentation(false)
+        .or_else(|| min_indentation(true))
+        .unwrap_or(0)
+}
+
+/// Count the attribute lines at the start of the body lines of a component
+///
+/// An attribute starts at a line that matches the attribute pattern in the config,
+/// and continues until the brackets in it are balanced.
+/// Brackets in strings and comments are ignored, see [`scan_brackets`].
+fn count_attribute_lines(lines: &[String], config: &Config) -> usize {
+    let pattern = match &config.attribute_pattern {
+        Some(pattern) => pattern,
+        None => return 0,
+    };
+    let mut count = 0;
+    while lines
+        .get(count)
+        .is_some_and(|line| pattern.is_match(line.trim_start()))
+    {
+        let brackets = scan_brackets(
+            &lines[count..],
+            &['(', '[', '{'],
+            &[')', ']', '}'],
+            has_lifetimes(config),
+        );
+        count += brackets
+            .iter()
+            .position(|(depth, _)| *depth == 0)
+            .map_or(brackets.len(), |i| i + 1);
+    }
+
+    count
+}
@@ -1,82 +1,191 @@
 //! Logic and tests for converting body lines to summary view
 
+use crate::process::Ellipsis;
+use crate::LineKind;
+
+/// A line in the summary view
+#[derive(Debug, Clone, PartialEq)]
+pub enum SummaryLine {
+    /// A line that is kept as-is, by its index in the input lines
+    Line(usize),
+    /// A block of lines that are elided, by the start (inclusive) and end (exclusive) indices in
+    /// the input lines
+    Elided(usize, usize),
+}
+
 /// Convert lines to summary view
 ///
-/// Consecutive lines or starts with a space or a tab will be replaced with `...` with the
+/// Consecutive lines or starts with a space or a tab will be replaced with the ellipsis with the
 /// specified indent. Empty lines inside indented region will be ignored while empty lines
 /// outside of indented region will be preserved.
 ///
 /// If exclude range is set, the lines in that range will be untouched.
 ///
-/// If indent is 0, this returns the vector as is
+/// The line numbers are the line numbers of the input lines in the file, and are used
+/// for rendering the ellipsis.
 ///
 /// The exclude range has inclusive start and exclusive end.
 pub fn summarize_lines(
     lines: &[String],
-    indent: usize,
+    line_numbers: &[usize],
+    indent: &str,
     exclude: Option<(usize, usize)>,
+    ellipsis: &Ellipsis,
 ) -> Vec<String> {
+    render_summary(
+        &summarize_line_ranges(lines, exclude),
+        lines,
+        line_numbers,
+        indent,
+        ellipsis,
+    )
+}
+
+/// Convert lines to summary view, without rendering the lines
+///
+/// See [`summarize_lines`] for how the lines are summarized.
+pub fn summarize_line_ranges(
+    lines: &[String],
+    exclude: Option<(usize, usize)>,
+) -> Vec<SummaryLine> {
+    summarize_line_ranges_by(lines, exclude, |_, line| {
+        line.starts_with(super::is_indent_char)
+    })
+}
+
+/// Convert lines to summary view, where the lines in the block are elided instead of the indented lines
+///
+/// This is used for components with brace nesting, where the block is the lines inside the braces.
+/// The exclude range works the same as [`summarize_lines`].
+pub fn summarize_block_line_ranges(
+    lines: &[String],
+    exclude: Option<(usize, usize)>,
+    block: (usize, usize),
+) -> Vec<SummaryLine> {
+    summarize_line_ranges_by(lines, exclude, |i, _| i >= block.0 && i < block.1)
+}
+
+/// Convert lines to summary view, where the lines are elided if `is_nested` returns true
+///
+/// `is_nested` is called with the index and the line. The exclude range works the same as
+/// [`summarize_lines`].
+pub fn summarize_line_ranges_by<F>(
+    lines: &[String],
+    exclude: Option<(usize, usize)>,
+    is_nested: F,
+) -> Vec<SummaryLine>
+where
+    F: Fn(usize, &str) -> bool,
+{
     let mut output = vec![];
-    let mut is_in_indent = false;
+    let mut elided_start: Option<usize> = None;
     for (i, line) in lines.iter().enumerate() {
-        if line.is_empty() && is_in_indent {
+        if line.is_empty() && elided_start.is_some() {
             continue;
         }
-        let should_ellipsize = line.starts_with(super::is_indent_char)
+        let should_ellipsize = is_nested(i, line)
             && match exclude {
                 Some((start, end)) => i < start || i >= end,
                 None => true,
             };
         if should_ellipsize {
-            if !is_in_indent {
-                output.push(super::indent_string("...", indent));
-                is_in_indent = true;
+            if elided_start.is_none() {
+                elided_start = Some(i);
             }
         } else {
-            output.push(line.clone());
-            is_in_indent = false;
+            if let Some(start) = elided_start.take() {
+                output.push(SummaryLine::Elided(start, i));
+            }
+            output.push(SummaryLine::Line(i));
         }
     }
+    if let Some(start) = elided_start {
+        output.push(SummaryLine::Elided(start, lines.len()));
+    }
 
     output
 }
 
+/// Render the summary view returned by [`summarize_line_ranges`]
+///
+/// The lines and line numbers should be the same ones used to create the summary view.
+pub fn render_summary(
+    summary: &[SummaryLine],
+    lines: &[String],
+    line_numbers: &[usize],
+    indent: &str,
+    ellipsis: &Ellipsis,
+) -> Vec<String> {
+    render_summary_with_kinds(summary, lines, line_numbers, indent, ellipsis)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect()
+}
+
+/// Render the summary view, with the kind of each line
+///
+/// The kept lines are [`LineKind::Target`] and the placeholders are [`LineKind::Ellipsis`].
+/// See [`render_summary`]
+pub fn render_summary_with_kinds(
+    summary: &[SummaryLine],
+    lines: &[String],
+    line_numbers: &[usize],
+    indent: &str,
+    ellipsis: &Ellipsis,
+) -> Vec<(LineKind, String)> {
+    summary
+        .iter()
+        .filter_map(|summary_line| match summary_line {
+            SummaryLine::Line(i) => Some((LineKind::Target, lines[*i].clone())),
+            SummaryLine::Elided(start, end) => ellipsis
+                .render(&line_numbers[*start..*end], indent)
+                .map(|line| (LineKind::Ellipsis, line)),
+        })
+        .collect()
+}
+
 /// Tests for summarize_lines
 #[cfg(test)]
 mod ut {
     use super::*;
 
+    fn summarize(lines: &[String], indent: usize, exclude: Option<(usize, usize)>) -> Vec<String> {
+        let line_numbers = (1..=lines.len()).collect::<Vec<_>>();
+        let indent = " ".repeat(indent);
+        summarize_lines(lines, &line_numbers, &indent, exclude, &Ellipsis::default())
+    }
+
     #[test]
     fn test_empty() {
         let expected: Vec<String> = vec![];
-        assert_eq!(summarize_lines(&[], 0, None), expected);
+        assert_eq!(summarize(&[], 0, None), expected);
     }
 
     #[test]
     fn test_no_indent() {
         let input = vec!["abc".to_string(), "bcd".to_string(), "cde".to_string()];
-        assert_eq!(summarize_lines(&input, 0, None), input);
+        assert_eq!(summarize(&input, 0, None), input);
     }
 
     #[test]
     fn test_indent_single_begin() {
         let input = vec![" abc".to_string(), "bcd".to_string(), "cde".to_string()];
         let expected = vec!["    ...".to_string(), "bcd".to_string(), "cde".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
     fn test_indent_single_middle() {
         let input = vec!["bcd".to_string(), " abc".to_string(), "cde".to_string()];
         let expected = vec!["bcd".to_string(), "    ...".to_string(), "cde".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
     fn test_indent_single_end() {
         let input = vec!["bcd".to_string(), "cde".to_string(), " abc".to_string()];
         let expected = vec!["bcd".to_string(), "cde".to_string(), "    ...".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -88,7 +197,7 @@ mod ut {
             "cde".to_string(),
         ];
         let expected = vec!["    ...".to_string(), "bcd".to_string(), "cde".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -100,7 +209,7 @@ mod ut {
             "cde".to_string(),
         ];
         let expected = vec!["bcd".to_string(), "    ...".to_string(), "cde".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -112,7 +221,7 @@ mod ut {
             "  abc".to_string(),
         ];
         let expected = vec!["bcd".to_string(), "cde".to_string(), "    ...".to_string()];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -133,7 +242,7 @@ mod ut {
             "cde".to_string(),
             "    ...".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -151,7 +260,7 @@ mod ut {
             "cde".to_string(),
             "    ...".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -169,7 +278,7 @@ mod ut {
             "    ...".to_string(),
             "cde".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 4, None), expected);
+        assert_eq!(summarize(&input, 4, None), expected);
     }
 
     #[test]
@@ -190,7 +299,7 @@ mod ut {
             "  ...".to_string(),
             "cde".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 2, Some((2, 4))), expected);
+        assert_eq!(summarize(&input, 2, Some((2, 4))), expected);
     }
 
     #[test]
@@ -213,7 +322,7 @@ mod ut {
             "  ...".to_string(),
             "cde".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 2, Some((3, 5))), expected);
+        assert_eq!(summarize(&input, 2, Some((3, 5))), expected);
     }
 
     #[test]
@@ -237,7 +346,7 @@ mod ut {
             " abc".to_string(),
             "cde".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 2, Some((4, 7))), expected);
+        assert_eq!(summarize(&input, 2, Some((4, 7))), expected);
     }
 
     #[test]
@@ -254,7 +363,7 @@ mod ut {
             "bcd".to_string(),
             "  ...".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 2, Some((1, 2))), expected);
+        assert_eq!(summarize(&input, 2, Some((1, 2))), expected);
     }
 
     #[test]
@@ -266,7 +375,7 @@ mod ut {
             " cde".to_string(),
         ];
         let expected = vec!["  ...".to_string(), "bcd".to_string(), "  ...".to_string()];
-        assert_eq!(summarize_lines(&input, 2, None), expected);
+        assert_eq!(summarize(&input, 2, None), expected);
     }
 
     #[test]
@@ -283,6 +392,65 @@ mod ut {
             "bcd".to_string(),
             "  ...".to_string(),
         ];
-        assert_eq!(summarize_lines(&input, 2, None), expected);
+        assert_eq!(summarize(&input, 2, None), expected);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let input = vec![
+            "bcd".to_string(),
+            " abc".to_string(),
+            "".to_string(),
+            "  abc".to_string(),
+            "cde".to_string(),
+            " abc".to_string(),
+        ];
+        let expected = vec![
+            SummaryLine::Line(0),
+            SummaryLine::Elided(1, 4),
+            SummaryLine::Line(4),
+            SummaryLine::Elided(5, 6),
+        ];
+        assert_eq!(summarize_line_ranges(&input, None), expected);
+    }
+
+    #[test]
+    fn test_ellipsis_template() {
+        let input = vec![
+            "bcd".to_string(),
+            " abc".to_string(),
+            "  abc".to_string(),
+            "cde".to_string(),
+        ];
+        let line_numbers = vec![10, 11, 12, 13];
+        let expected = vec![
+            "bcd".to_string(),
+            "  // 2 lines (11-12)".to_string(),
+            "cde".to_string(),
+        ];
+        let ellipsis = Ellipsis::new("// {lines} lines ({start}-{end})");
+        assert_eq!(
+            summarize_lines(&input, &line_numbers, "  ", None, &ellipsis),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_block_ranges() {
+        let input = vec![
+            "namespace a {".to_string(),
+            "int x;".to_string(),
+            "".to_string(),
+            "  int y;".to_string(),
+            "}".to_string(),
+            "".to_string(),
+        ];
+        let expected = vec![
+            SummaryLine::Line(0),
+            SummaryLine::Elided(1, 4),
+            SummaryLine::Line(4),
+            SummaryLine::Line(5),
+        ];
+        assert_eq!(summarize_block_line_ranges(&input, None, (1, 4)), expected);
     }
 }
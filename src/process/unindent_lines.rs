@@ -10,8 +10,27 @@
 ///
 /// Empty lines are only removed if the first non-empty line above it is also removed.
 pub fn unindent_lines(lines: &[String], indent: usize) -> Vec<String> {
+    unindent_lines_enumerated(lines, indent)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect()
+}
+
+/// Same as [`unindent_lines`], but also returns the index of each line in the input
+pub fn unindent_lines_enumerated(lines: &[String], indent: usize) -> Vec<(usize, String)> {
+    unindent_lines_keeping(lines, indent, |_| false)
+}
+
+/// Same as [`unindent_lines_enumerated`], but the lines where `keep` returns true are never removed
+///
+/// This is used for the lines inside string literals. Those lines are unindented by at most
+/// their own indentation.
+pub fn unindent_lines_keeping<F>(lines: &[String], indent: usize, keep: F) -> Vec<(usize, String)>
+where
+    F: Fn(usize) -> bool,
+{
     if indent == 0 {
-        return lines.to_vec();
+        return lines.iter().cloned().enumerate().collect();
     }
 
     // starting assuming lines are not indented and removed
@@ -19,16 +38,23 @@ pub fn unindent_lines(lines: &[String], indent: usize) -> Vec<String> {
 
     lines
         .iter()
-        .filter_map(|line| {
-            if line.is_empty() {
+        .enumerate()
+        .filter_map(|(i, line)| {
+            if keep(i) {
+                let line_indent = line.len() - line.trim_start_matches(super::is_indent_char).len();
+                last_non_empty_removed = false;
+                Some((i, line[line_indent.min(indent)..].to_string()))
+            } else if line.is_empty() {
                 if last_non_empty_removed {
                     None
                 } else {
-                    Some("".to_string())
+                    Some((i, "".to_string()))
                 }
             } else if line.starts_with(super::is_indent_char) {
                 last_non_empty_removed = false;
-                Some(line[indent..].to_string())
+                // lines shorter than the indentation, like ` */`, are only trimmed
+                let line = line.get(indent..).unwrap_or(line.trim_start());
+                Some((i, line.to_string()))
             } else {
                 last_non_empty_removed = true;
                 None
@@ -141,4 +167,40 @@ mod ut {
         let expected: Vec<String> = vec!["abc".to_string(), "abc2".to_string()];
         assert_eq!(unindent_lines(&input, 1), expected);
     }
+
+    #[test]
+    fn test_enumerated() {
+        let input: Vec<String> = vec![
+            "abc".to_string(),
+            "".to_string(),
+            " abc".to_string(),
+            "".to_string(),
+            "\tabc2".to_string(),
+            "abc".to_string(),
+        ];
+        let expected: Vec<(usize, String)> = vec![
+            (2, "abc".to_string()),
+            (3, "".to_string()),
+            (4, "abc2".to_string()),
+        ];
+        assert_eq!(unindent_lines_enumerated(&input, 1), expected);
+    }
+
+    #[test]
+    fn test_keeping() {
+        let input: Vec<String> = vec![
+            "  abc".to_string(),
+            "abc".to_string(),
+            "".to_string(),
+            "   abc".to_string(),
+            "abc2".to_string(),
+        ];
+        let expected: Vec<(usize, String)> = vec![
+            (0, "abc".to_string()),
+            (1, "abc".to_string()),
+            (2, "".to_string()),
+            (3, " abc".to_string()),
+        ];
+        assert_eq!(unindent_lines_keeping(&input, 2, |i| i == 1), expected);
+    }
 }
@@ -0,0 +1,145 @@
+//! Rendering of the codump directives in documentation files
+
+use std::path::Path;
+
+use clap::Parser;
+
+use crate::{execute_multiple, CliArgs, Config, Output};
+
+/// Prefix of the directive lines in AsciiDoc files, which is a comment
+const ASCIIDOC_DIRECTIVE: &str = "// codump:";
+/// Prefix of the directive lines in reStructuredText files, which is a comment
+const RST_DIRECTIVE: &str = ".. codump:";
+
+/// Render the directives in the file
+///
+/// The output mode of each directive is the one of the file, detected from the extension
+/// (`.adoc`/`.asciidoc` or `.rst`). The paths in the directives are relative to the current
+/// directory, like on the command line. The source blocks in reStructuredText files are
+/// indented like the directive, so directives can be used in lists.
+///
+/// On success, returns the rendered file as a vector of lines.
+/// On failure, returns an error message with the line of the directive.
+pub fn render_file(file: &str) -> Result<Vec<String>, String> {
+    let (prefix, output) = match Path::new(file).extension().and_then(|e| e.to_str()) {
+        Some("adoc" | "asciidoc") => (ASCIIDOC_DIRECTIVE, Output::Asciidoc),
+        Some("rst") => (RST_DIRECTIVE, Output::Rst),
+        _ => {
+            return Err(format!(
+                "Cannot render {file}: only .adoc, .asciidoc and .rst files are supported"
+            ))
+        }
+    };
+    let content = match std::fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => return Err(format!("io error while reading {file}: {e}")),
+    };
+
+    let mut lines = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let args = match trimmed.strip_prefix(prefix) {
+            Some(args) => args,
+            None => {
+                lines.push(line.to_string());
+                continue;
+            }
+        };
+        let indent = &line[..line.len() - trimmed.len()];
+        let block =
+            render_directive(args, output.clone()).map_err(|e| format!("{file}:{}: {e}", i + 1))?;
+        for block_line in block {
+            if block_line.is_empty() || output == Output::Asciidoc {
+                lines.push(block_line);
+            } else {
+                lines.push(format!("{indent}{block_line}"));
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Run codump with the arguments of a directive
+fn render_directive(args: &str, output: Output) -> Result<Vec<String>, String> {
+    let mut args = split_args(args)?;
+    args.insert(0, "codump".to_string());
+    let args = CliArgs::try_parse_from(args).map_err(|e| e.to_string().trim().to_string())?;
+    let file = args.file.clone();
+    let search_paths = args.search_paths();
+    let mut config: Config = args.try_into()?;
+    config.output = output;
+    execute_multiple(&file, &search_paths, &config)
+}
+
+/// Split the arguments of a directive like a shell
+///
+/// Arguments are separated by whitespace, and can be quoted with `'` or `"`.
+/// Inside `"`, `\"` and `\\` are escapes.
+fn split_args(args: &str) -> Result<Vec<String>, String> {
+    let mut result = vec![];
+    let mut current: Option<String> = None;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    result.push(arg);
+                }
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => arg.push(c),
+                        None => return Err("Unterminated ' in the directive".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => arg.push(c),
+                            Some(c) => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                            None => return Err("Unterminated \" in the directive".to_string()),
+                        },
+                        Some(c) => arg.push(c),
+                        None => return Err("Unterminated \" in the directive".to_string()),
+                    }
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(arg) = current {
+        result.push(arg);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#" -p rust src/lib.rs "fn execute" '-c'  "#).unwrap(),
+            vec!["-p", "rust", "src/lib.rs", "fn execute", "-c"]
+        );
+        assert_eq!(
+            split_args(r#""say \"hi\"" 'a\b' x""y"#).unwrap(),
+            vec![r#"say "hi""#, r"a\b", "xy"]
+        );
+        assert_eq!(split_args(r#"''"#).unwrap(), vec![""]);
+        assert!(split_args(r#""fn execute"#).is_err());
+        assert!(split_args("'fn").is_err());
+    }
+}
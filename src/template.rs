@@ -0,0 +1,310 @@
+//! Logic and tests for formatting components with custom templates
+
+use regex::Regex;
+
+use crate::process::{component_comment_text, Component, Context};
+use crate::{format_context, trim_empty_lines, Config};
+
+/// Names of the placeholders available in templates
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "outer_comments",
+    "attributes",
+    "inner_comments",
+    "comment_text",
+    "signature",
+    "summary",
+    "body",
+    "file",
+    "search_path",
+    "span",
+    "context_prefix",
+    "context_suffix",
+    "indent",
+];
+
+/// A part of a parsed template
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum TemplatePart {
+    /// Text that is printed as-is
+    Text(String),
+    /// `{name}`, replaced by the value of the placeholder
+    Placeholder(String),
+    /// `{#name}...{/name}`, printed if the value of the placeholder is not empty,
+    /// or `{^name}...{/name}`, printed if the value is empty
+    Section {
+        name: String,
+        inverted: bool,
+        parts: Vec<TemplatePart>,
+    },
+}
+
+/// A parsed output template
+///
+/// Placeholders are written as `{name}`. `{{` and `}}` are printed as `{` and `}`.
+/// Lines that only have a section tag are removed, so sections can be on their own lines.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+/// Data of a found component for rendering a template
+pub struct TemplateData<'a> {
+    /// Path of the file
+    pub file: &'a str,
+    /// Search path of the component
+    pub search_path: &'a [String],
+    /// The found component
+    pub component: &'a Component,
+    /// Context of the component, in the same order as returned by find_component
+    pub contexts: &'a [Context],
+}
+
+/// Implementation of Template
+impl Template {
+    /// Parse a template string
+    ///
+    /// Returns an error if a placeholder is unknown, or if a placeholder or section is not closed
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let standalone = Regex::new(r"^\s*(\{[#^/][^{}]*\})\s*$").unwrap();
+        let source = source
+            .split_inclusive('\n')
+            .map(|line| match standalone.captures(line) {
+                Some(captures) => captures[1].to_string(),
+                None => line.to_string(),
+            })
+            .collect::<String>();
+        // stack of the sections being parsed, with the name and if the section is inverted
+        let mut stack: Vec<(String, bool, Vec<TemplatePart>)> = vec![];
+        let mut parts = vec![];
+        let mut text = String::new();
+        let mut rest = source.as_str();
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                text.push(c);
+                rest = &rest[2..];
+                continue;
+            }
+            if c != '{' {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => return Err(format!("Unclosed placeholder in template: \"{rest}\"")),
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            if !text.is_empty() {
+                parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+            }
+            let (name, kind) = match tag.strip_prefix(['#', '^', '/']) {
+                Some(name) => (name, tag.chars().next()),
+                None => (tag, None),
+            };
+            if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Unknown placeholder \"{name}\" in template. Available placeholders: {}",
+                    TEMPLATE_PLACEHOLDERS.join(", ")
+                ));
+            }
+            match kind {
+                Some('/') => match stack.pop() {
+                    Some((open, inverted, outer)) if open == name => {
+                        let section = TemplatePart::Section {
+                            name: open,
+                            inverted,
+                            parts: std::mem::replace(&mut parts, outer),
+                        };
+                        parts.push(section);
+                    }
+                    _ => return Err(format!("Unexpected \"{{/{name}}}\" in template")),
+                },
+                Some(kind) => {
+                    let outer = std::mem::take(&mut parts);
+                    stack.push((name.to_string(), kind == '^', outer));
+                }
+                None => parts.push(TemplatePart::Placeholder(name.to_string())),
+            }
+        }
+        if let Some((name, _, _)) = stack.pop() {
+            return Err(format!("Section \"{name}\" is not closed in template"));
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Render the template for a component
+    pub fn render(&self, data: &TemplateData, config: &Config) -> Vec<String> {
+        let mut output = String::new();
+        render_parts(&self.parts, &|name| data.value(name, config), &mut output);
+        output.lines().map(|line| line.to_string()).collect()
+    }
+}
+
+/// Render the template parts to the output, with the values returned by `value`
+///
+/// Each line of a multi-line value is prefixed with the text before the placeholder
+/// on the same line, like `> ` for quotes.
+fn render_parts(parts: &[TemplatePart], value: &dyn Fn(&str) -> String, output: &mut String) {
+    for part in parts {
+        match part {
+            TemplatePart::Text(text) => output.push_str(text),
+            TemplatePart::Placeholder(name) => {
+                let line_start = output.rfind('\n').map_or(0, |i| i + 1);
+                let prefix = output[line_start..].to_string();
+                for (i, line) in value(name).split('\n').enumerate() {
+                    if i > 0 {
+                        output.push('\n');
+                        if line.is_empty() {
+                            output.push_str(prefix.trim_end());
+                        } else {
+                            output.push_str(&prefix);
+                        }
+                    }
+                    output.push_str(line);
+                }
+            }
+            TemplatePart::Section {
+                name,
+                inverted,
+                parts,
+            } => {
+                if value(name).is_empty() == *inverted {
+                    render_parts(parts, value, output);
+                }
+            }
+        }
+    }
+}
+
+/// Implementation of TemplateData
+impl TemplateData<'_> {
+    /// Get the value of a placeholder. Multiple lines are joined with `\n`
+    fn value(&self, name: &str, config: &Config) -> String {
+        let component = self.component;
+        let lines = match name {
+            "outer_comments" => component.outer_comments.clone(),
+            "attributes" => component.attributes.clone(),
+            "inner_comments" => component.inner_comments.clone(),
+            "comment_text" => trim_empty_lines(&component_comment_text(component, config)).to_vec(),
+            "signature" => match component.signature {
+                Some((start, end)) => component.body_lines[start..end]
+                    .iter()
+                    .map(|line| line.trim_end().to_string())
+                    .collect(),
+                None => component
+                    .body_lines
+                    .iter()
+                    .find(|line| !line.trim().is_empty())
+                    .map(|line| line.trim_end().to_string())
+                    .into_iter()
+                    .collect(),
+            },
+            "summary" => component.summarize(&config.ellipsis),
+            "body" => component.body_lines.clone(),
+            "file" => vec![self.file.to_string()],
+            "search_path" => vec![self.search_path.join(" ")],
+            // the end of the span is exclusive
+            "span" => vec![format!(
+                "{}-{}",
+                component.span.0,
+                component.span.1.saturating_sub(1).max(component.span.0)
+            )],
+            "context_prefix" | "context_suffix" => {
+                let lines = format_context(&[], self.contexts);
+                let prefix_len = self
+                    .contexts
+                    .iter()
+                    .map(|context| context.outer_comments.len() + context.begin_body_lines.len())
+                    .sum();
+                if name == "context_prefix" {
+                    lines[..prefix_len].to_vec()
+                } else {
+                    lines[prefix_len..].to_vec()
+                }
+            }
+            "indent" => {
+                let indent = self
+                    .contexts
+                    .iter()
+                    .rev()
+                    .map(|context| context.indent_prefix.as_str())
+                    .collect::<String>();
+                vec![indent]
+            }
+            _ => vec![],
+        };
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod ut {
+    use super::*;
+
+    fn render(source: &str, value: &dyn Fn(&str) -> String) -> String {
+        let mut output = String::new();
+        render_parts(&Template::parse(source).unwrap().parts, value, &mut output);
+        output
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let value = |name: &str| name.to_uppercase();
+        assert_eq!(
+            render("{{{file}}}: {span}\n{body}", &value),
+            "{FILE}: SPAN\nBODY"
+        );
+    }
+
+    #[test]
+    fn test_standalone_tags() {
+        let value = |name: &str| name.to_string();
+        assert_eq!(
+            render(
+                "a\n  {#body}\n{body}\n{/body}  \nb {^body}{/body}\n",
+                &value
+            ),
+            "a\nbody\nb \n"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_prefix() {
+        let value = |_: &str| "a\n\nb".to_string();
+        assert_eq!(render("x\n> {body}!", &value), "x\n> a\n>\n> b!");
+    }
+
+    #[test]
+    fn test_sections() {
+        let value = |name: &str| match name {
+            "file" => "a.rs".to_string(),
+            _ => String::new(),
+        };
+        assert_eq!(
+            render("{#file}in {file}{/file}{#body}body{/body}", &value),
+            "in a.rs"
+        );
+        assert_eq!(
+            render("{^body}no body{#file}, {file}{/file}{/body}", &value),
+            "no body, a.rs"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(Template::parse("{foo}").is_err());
+        assert!(Template::parse("{body").is_err());
+        assert!(Template::parse("{#body}").is_err());
+        assert!(Template::parse("{#body}{/file}").is_err());
+        assert!(Template::parse("}{/body}").is_err());
+        assert!(Template::parse("a } b").is_ok());
+    }
+}
@@ -0,0 +1,31 @@
+//! Components with decorators
+
+/**
+ * A component with a multi-line decorator
+ */
+@Component({
+    selector: "app-hello",
+    template: "<p>Hello</p>",
+})
+export class HelloComponent {
+    /**
+     * The name to greet
+     */
+    @Input()
+    @Pattern({ regexp: "[a-z(" })
+    name: string = "";
+
+    /**
+     * Greet the name
+     */
+    @HostListener("click")
+    greet() {
+        console.log(`Hello ${this.name}`);
+    }
+}
+
+/**
+ * A plain class
+ */
+export class Plain {
+}
@@ -0,0 +1,13 @@
+/// A class
+class A {
+    /// The method
+    f() {
+        return 0;
+    }
+}
+
+class B {
+    g() {
+        return 1;
+    }
+}
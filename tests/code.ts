@@ -0,0 +1,17 @@
+/**
+ * Area of a rectangle
+ */
+export function area(width: number, height: number): number {
+    /*
+     * Multiply the sides
+     */
+    const area = width * height; /* the area */
+    /* single line block comment */
+    const url = "http://example.com/*";
+    // line comment
+    const path = "/* not a comment */";
+    const banner = `
+// not a comment either
+`;
+    return area;
+}
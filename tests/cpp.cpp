@@ -0,0 +1,39 @@
+//! C++ code in namespaces is usually not indented
+
+#include <string>
+
+/// The namespace
+namespace hello {
+
+/// A greeter
+class Greeter {
+public:
+    /// Create a greeter
+    ///
+    /// @param name Name to greet
+    explicit Greeter(std::string name) : name_(std::move(name)) {}
+
+    /**
+     * Greet the name
+     * @returns The greeting
+     */
+    std::string greet() const {
+        // braces in strings and comments are ignored: }
+        return "Hello, " + name_ + "}";
+    }
+
+private:
+    std::string name_;
+};
+
+/// Add two numbers
+int add(int a, int b)
+{
+    return a + b;
+}
+
+/// The answer
+constexpr int ANSWER =
+    42;
+
+} // namespace hello
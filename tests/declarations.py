@@ -0,0 +1,25 @@
+"""Undocumented code can be found by the declarations"""
+
+import functools
+
+#: Default greeting
+GREETING = "Hello"
+
+def undocumented(name):
+    greeting = GREETING
+    return greeting + name
+
+@functools.cache
+def cached(name):
+    return undocumented(name)
+
+class Greeter:
+    def __init__(self, name):
+        self.name = name
+
+    async def greet(self):
+        """Greets with the name"""
+        return cached(self.name)
+
+if __name__ == "__main__":
+    print(undocumented("World"))
@@ -0,0 +1,13 @@
+{-! Haskell file with nested comments -}
+
+{-| Add two numbers
+
+{- This is nested -}
+and still in the comment
+-}
+add :: Int -> Int -> Int
+add a b = a + b
+
+-- | Double a number
+double :: Int -> Int
+double a = a * 2
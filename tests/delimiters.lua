@@ -0,0 +1,15 @@
+--[[! Lua file with long bracket comments ]]
+
+--[==[
+Add two numbers.
+
+The closer ]] does not end this comment
+]==]
+local function add(a, b)
+    return a + b
+end
+
+--- Greet someone
+function greet(name)
+    print("Hello " .. name)
+end
@@ -0,0 +1,8 @@
+"""Python file with docstrings"""
+
+def foo():
+    """Docstring with the other quotes
+
+    so the docstring does not end at '''
+    """
+    return 1
@@ -0,0 +1,10 @@
+class Protocol:
+    """Methods of protocols have ... as the body"""
+
+    def method(self):
+        """Body is a literal ellipsis"""
+        ...
+
+    def other(self):
+        """Other method"""
+        return 1
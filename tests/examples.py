@@ -0,0 +1,12 @@
+"""Module with doctests"""
+
+def add(a, b):
+    """Add two numbers
+
+    >>> add(1, 2)
+    3
+    >>> add(
+    ...     -1, 1)
+    0
+    """
+    return a + b
@@ -0,0 +1,38 @@
+//! Module with examples
+//!
+//! ```ts
+//! import { add } from "./examples";
+//! ```
+//!
+//! Run with:
+//!
+//! ```sh
+//! npx ts-node examples.ts
+//! ```
+
+/**
+ * Add two numbers
+ *
+ * ```ts
+ * const x = add(1, 2);
+ * console.log(x);
+ * ```
+ *
+ * With negative numbers:
+ *
+ * ```typescript
+ * if (add(-1, 1) !== 0) {
+ *     throw new Error();
+ * }
+ * ```
+ */
+export function add(a: number, b: number): number {
+    /// Helper
+    ///
+    /// ```
+    /// helper();
+    /// ```
+    function helper() {
+    }
+    return a + b;
+}
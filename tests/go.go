@@ -0,0 +1,35 @@
+// Package shapes has geometric shapes.
+package shapes
+
+// Copyright notice, not a doc comment
+
+import "math"
+
+// Point is a point in the plane.
+type Point struct {
+	// X coordinate
+	X float64
+	// Y coordinate
+	Y float64
+}
+
+// Dist returns the distance to another point.
+//
+//go:noinline
+func (p Point) Dist(q Point) float64 {
+	// difference of the coordinates
+	dx := p.X - q.X
+	dy := p.Y - q.Y
+	return math.Sqrt(dx*dx + dy*dy)
+}
+
+// Shape is anything with an area.
+type Shape interface {
+	// Area returns the area.
+	Area() float64
+}
+
+/*
+Origin is the origin.
+*/
+var Origin = Point{}
@@ -0,0 +1,14 @@
+"""Strings that look like code and comments"""
+
+def render(name):
+    """Render the greeting"""
+    return f"""
+Hello {name}
+
+def fake():
+    '''Not a function'''
+"""
+
+def after():
+    """Found after the string"""
+    pass
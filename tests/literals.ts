@@ -0,0 +1,13 @@
+/** Query builder */
+export class Query {
+    /** The SQL */
+    sql = `
+/** Not a doc comment */
+SELECT 1
+`;
+
+    /** Run the query */
+    run() {
+        return this.sql;
+    }
+}
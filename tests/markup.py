@@ -0,0 +1,36 @@
+"""Module with docstrings"""
+
+def divide(a, b):
+    """Divide two numbers
+
+    Args:
+        a (float): The dividend
+        b (float): The divisor,
+            which must not be 0
+
+    Returns:
+        float: The quotient
+
+    Raises:
+        ZeroDivisionError: If b is 0
+    """
+    return a / b
+
+def divmod_numbers(a, b):
+    """Divide two numbers with the remainder
+
+    Parameters
+    ----------
+    a : int
+        The dividend
+    b : int
+        The divisor
+
+    Returns
+    -------
+    quotient : int
+        The quotient
+    remainder : int
+        The remainder
+    """
+    return a // b, a % b
@@ -0,0 +1,14 @@
+//! Module with JSDoc
+
+/**
+ * Divide two numbers, see {@link multiply}
+ *
+ * @param {number} a The dividend
+ * @param {number} b The divisor,
+ *   which must not be {@code 0}
+ * @returns {number} The quotient
+ * @throws {RangeError} If `b` is 0
+ */
+export function divide(a: number, b: number): number {
+    return a / b;
+}
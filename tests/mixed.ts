@@ -0,0 +1,18 @@
+//! Components with mixed comment styles
+
+/**
+ * Add two numbers
+ */
+/// Both numbers must be finite
+export function add(a: number, b: number): number {
+    return a + b;
+}
+
+/// Read the value at the index
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+/**
+ * The index is not checked
+ */
+export function read(values: any[], index: number): any {
+    return values[index];
+}
@@ -41,9 +41,9 @@ macro_rules! testit {
 
                 let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
                 let file = args.file.clone();
-                let search_path = args.search_path.clone();
+                let search_paths = args.search_paths();
                 let config = args.try_into().expect("Failed to parse config");
-                let output = codump::execute(&file, &search_path, &config)
+                let output = codump::execute_multiple(&file, &search_paths, &config)
                     .expect("Failed to execute codump");
 
                 assert_eq!(output, expected);
@@ -56,4 +56,205 @@ testit!(main);
 testit!(presets);
 testit!(format);
 testit!(typescript);
+testit!(blocks);
 testit!(python);
+testit!(expand);
+testit!(multiple);
+testit!(siblings);
+testit!(breadcrumb);
+testit!(json);
+testit!(html);
+testit!(docs);
+testit!(examples);
+testit!(markup);
+testit!(stub);
+testit!(code);
+testit!(max_lines);
+testit!(template);
+testit!(cpp);
+testit!(attributes);
+testit!(signature);
+testit!(mixed);
+testit!(delimiters);
+testit!(literals);
+testit!(go);
+testit!(trailing);
+testit!(declarations);
+testit!(sections);
+
+/// Custom formatter that prints the first line of the component and the number of parents
+struct OutlineFormatter;
+
+impl codump::Formatter for OutlineFormatter {
+    fn format_component(
+        &self,
+        component: &codump::process::Component,
+        contexts: &[codump::process::Context],
+        _config: &codump::Config,
+    ) -> Vec<String> {
+        let first = component.body_lines.first().cloned().unwrap_or_default();
+        vec![format!("{first} ({} parents)", contexts.len())]
+    }
+}
+
+#[test]
+fn custom_formatter() {
+    let args = [
+        "codump",
+        "-p",
+        "rust-java",
+        "tests/typescript.ts",
+        "export class Hello",
+        "constructor",
+        "-c",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let search_paths = args.search_paths();
+    let config = args.try_into().expect("Failed to parse config");
+    let output = codump::execute_with_formatter(
+        "tests/typescript.ts",
+        &search_paths,
+        &OutlineFormatter,
+        &config,
+    )
+    .expect("Failed to execute codump");
+
+    assert_eq!(
+        output,
+        vec![
+            "// ...",
+            "export class Hello {",
+            "    constructor(name: string) { (2 parents)",
+            "}",
+        ]
+    );
+}
+
+#[test]
+fn custom_formatter_with_max_lines() {
+    let args = [
+        "codump",
+        "-p",
+        "rust-java",
+        "tests/typescript.ts",
+        "export class Hello",
+        "--max-lines",
+        "5",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let search_paths = args.search_paths();
+    let config = args.try_into().expect("Failed to parse config");
+    let result = codump::execute_with_formatter(
+        "tests/typescript.ts",
+        &search_paths,
+        &OutlineFormatter,
+        &config,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn context_longer_than_max_lines() {
+    let args = [
+        "codump",
+        "-p",
+        "rust-java",
+        "tests/typescript.ts",
+        "export class Hello",
+        "constructor",
+        "-c",
+        "--max-lines",
+        "3",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let file = args.file.clone();
+    let search_paths = args.search_paths();
+    let config = args.try_into().expect("Failed to parse config");
+    let result = codump::execute_multiple(&file, &search_paths, &config);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn max_lines_zero() {
+    let args = [
+        "codump",
+        "-p",
+        "rust-java",
+        "tests/typescript.ts",
+        "export class Hello",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let config = args.try_into().expect("Failed to parse config");
+    let root = codump::parse_file("tests/typescript.ts", &config).expect("Failed to parse file");
+    let output = codump::format_max_lines(&root, 0, &config);
+
+    assert_eq!(output.len(), 1);
+}
+
+#[test]
+fn local_variables_are_not_items() {
+    let args = [
+        "codump",
+        "-p",
+        "python",
+        "tests/declarations.py",
+        "def undocumented",
+        "greeting",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let file = args.file.clone();
+    let search_paths = args.search_paths();
+    let config = args.try_into().expect("Failed to parse config");
+    let result = codump::execute_multiple(&file, &search_paths, &config);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn nested_search_paths() {
+    let args = [
+        "codump",
+        "-p",
+        "python",
+        "tests/python.py",
+        "class Hello",
+        "--and",
+        "class Hello",
+        "__init__",
+    ];
+    let args = codump::CliArgs::try_parse_from(args).expect("Failed to parse args");
+    let file = args.file.clone();
+    let search_paths = args.search_paths();
+    let config = args.try_into().expect("Failed to parse config");
+    let result = codump::execute_multiple(&file, &search_paths, &config);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn render() {
+    let test = std::fs::read_to_string("tests/render.toml")
+        .unwrap()
+        .parse::<toml::Table>()
+        .unwrap();
+    for test in test["test"].as_array().unwrap() {
+        let file = test["file"]
+            .as_str()
+            .expect("TOML test definition is missing the file");
+        let expected = test["out"]
+            .as_str()
+            .expect("TOML test definition is missing the expected output")
+            .lines()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+
+        let args = codump::CliArgs::try_parse_from(["codump", "--render", file])
+            .expect("Failed to parse args");
+        let file = args.render.expect("The file to render is missing");
+        let output = codump::render_file(&file).expect("Failed to render file");
+
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,17 @@
+/// Geometry helpers
+namespace geometry {
+
+#pragma region Points
+
+/// A point
+struct Point {
+    int x;
+    int y;
+};
+
+#pragma endregion
+
+/// Distance between points
+int dist(Point a, Point b);
+
+}
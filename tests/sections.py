@@ -0,0 +1,16 @@
+"""Sections group the members of a module or class"""
+
+# region Math
+
+def add(a, b):
+    return a + b
+
+# MARK: Products
+
+def mul(a, b):
+    return a * b
+
+# endregion
+
+def main():
+    print(add(1, mul(2, 3)))
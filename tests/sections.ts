@@ -0,0 +1,31 @@
+/// Networking client
+export class Client {
+    /// Creates a client
+    constructor(private url: string) {}
+
+    // MARK: - Networking
+
+    /// Sends a GET request
+    get(path: string) {
+        return fetch(this.url + path);
+    }
+
+    /// Sends a POST request
+    post(path: string, body: string) {
+        return fetch(this.url + path, { method: "POST", body });
+    }
+
+    /// # Parsing helpers
+
+    /// Parses a number
+    parseNumber(s: string): number {
+        return Number(s);
+    }
+
+    // #region Internal
+
+    /// Cache of the responses
+    private cache = new Map();
+
+    // #endregion
+}
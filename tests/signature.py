@@ -0,0 +1,14 @@
+"""Module"""
+
+def foo(
+    a: int,
+    b: list[
+        int
+    ],
+) -> int:
+    """Foo"""
+    return a
+
+X = [
+    1,
+]
@@ -0,0 +1,10 @@
+/// A string with an open brace
+const s = 'ab{';
+
+/// A class
+class A {
+}
+
+/// A function
+function c() {
+}
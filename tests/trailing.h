@@ -0,0 +1,22 @@
+//! Pixels and colors
+
+/// Colors of a pixel
+enum Color {
+    Red,   ///< the red channel
+    Green, ///< the green channel
+    Blue,
+    ///< the blue channel
+    ///< (also used for the sky)
+    /// Transparency
+    Alpha,
+};
+
+/// A pixel
+struct Pixel {
+    int x; /**< horizontal position */
+    int y; //!< vertical position
+    const char *label = "pixel ///< label";
+};
+
+///Add two numbers
+int add(int a, int b);
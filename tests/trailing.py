@@ -0,0 +1,13 @@
+"""Settings"""
+
+#: Default port
+PORT = 8080
+HOST = "localhost"  #: Default host
+
+
+class Config:
+    """Config"""
+
+    timeout = 30  #: Timeout in seconds
+    #: Number of retries
+    retries = 3